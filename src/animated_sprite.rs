@@ -1,59 +1,375 @@
-use std::time::{Duration, Instant};
-use image::{ RgbaImage};
-use std::fs;
+use image::RgbaImage;
+use crate::assets::Assets;
+use crate::composite::{self, Anchor, Placement};
+use crate::config::Config;
+use crate::error::GameError;
 
+// Sprite animado con clips con nombre. Se describe con un archivo de atlas:
+//
+//   [sheet]
+//   image = "enemy_sheet.png"     # hoja empaquetada...
+//   frame_width = 64
+//   frame_height = 64
+//   frames = a.png, b.png         # ...o cuadros sueltos
+//   directions = 8                # opcional: un bloque de cuadros por angulo
+//
+//   [clip.walk]
+//   frames = 0, 1, 2, 3
+//   duration = 120                # ms; uno por cuadro o uno para todos
+//   mode = loop                   # loop | once | pingpong
+//   event = 1:cue:sfx.footsteps   # cuadro:nombre, se puede repetir
+//
+//   [placement]                   # solo para sprites superpuestos en pantalla
+//   anchor = bottom               # top_left, top, ..., center, ..., bottom_right
+//   scale = 1.5
+//   offset_x = 0
+//   offset_y = -10
+//
+// Con `directions = N` los cuadros se dividen en N bloques iguales, uno por
+// angulo de vista (0 = de frente a la camara, luego girando en sentido
+// creciente del angulo). Los clips numeran los cuadros dentro de un bloque.
+#[derive(Clone)]
 pub struct AnimatedSprite {
+    pub placement: Placement,
+    // El atlas y las imagenes de donde salio, para recargarlo si cambian
+    files: Vec<String>,
     frames: Vec<RgbaImage>,
-    frame_duration: Duration,
+    directions: usize,
+    clips: Vec<Clip>,
+    current_clip: usize,
     current_frame: usize,
-    last_update: Instant,
+    elapsed: f64,
+    reverse: bool,
+    finished: bool,
+    // Eventos del primer cuadro de un clip recien empezado; salen en el
+    // siguiente `update` junto con los demas
+    pending: Vec<FrameEvent>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PlayMode {
+    Loop,
+    Once,
+    PingPong,
+}
+
+#[derive(Clone)]
+pub struct Clip {
+    pub name: String,
+    frames: Vec<usize>,
+    durations: Vec<f64>,
+    mode: PlayMode,
+    events: Vec<(usize, String)>,
+}
+
+// Se emite cuando un clip entra a un cuadro que tiene un evento
+#[derive(Clone, Debug)]
+pub struct FrameEvent {
+    pub name: String,
 }
 
 impl AnimatedSprite {
-    pub fn new(path: &str, frame_count: usize, frame_duration: Duration) -> Result<Self, String> {
-        let mut frames = Vec::with_capacity(frame_count);
-        for i in 0..frame_count {
-            let file_path = format!("{}_{}.png", path, i); 
-            println!("Attempting to open: {}", file_path); 
-            if !fs::metadata(&file_path).is_ok() {
-                return Err(format!("File not found: {}", file_path));
+    pub fn from_atlas(assets: &Assets, atlas_file: &str) -> Result<Self, GameError> {
+        let invalid = |e: String| GameError::config(atlas_file, e);
+        let atlas = Config::parse(&assets.read_to_string(atlas_file)?).map_err(invalid)?;
+        let frames = load_frames(assets, &atlas, atlas_file)?;
+        let directions = atlas.get_f64("sheet", "directions").map_err(invalid)?.unwrap_or(1.0) as usize;
+        if directions == 0 || frames.len() % directions != 0 {
+            return Err(invalid(format!("{} frames can't be split into {} directions", frames.len(), directions)));
+        }
+
+        let mut clips = Vec::new();
+        for section in atlas.section_names() {
+            if let Some(name) = section.strip_prefix("clip.") {
+                let clip = parse_clip(&atlas, section, name, frames.len() / directions).map_err(invalid)?;
+                clips.push(clip);
             }
-            let img = image::open(file_path).map_err(|e| e.to_string())?;
-            frames.push(img.to_rgba8()); 
         }
-    
-        Ok(AnimatedSprite {
+        if clips.is_empty() {
+            return Err(invalid("no [clip.*] sections".to_string()));
+        }
+        let placement = Placement::from_config(&atlas, "placement", Placement::new(Anchor::Bottom)).map_err(invalid)?;
+
+        let mut files = vec![atlas_file.to_string()];
+        files.extend(image_files(&atlas).map(str::to_string));
+
+        Ok(AnimatedSprite::new(placement, files, frames, directions, clips))
+    }
+
+    // Empieza con el primer clip del atlas
+    fn new(placement: Placement, files: Vec<String>, frames: Vec<RgbaImage>, directions: usize, clips: Vec<Clip>) -> Self {
+        let mut sprite = AnimatedSprite {
+            placement,
+            files,
             frames,
-            frame_duration,
+            directions,
+            clips,
+            current_clip: 0,
             current_frame: 0,
-            last_update: Instant::now(),
-        })
+            elapsed: 0.0,
+            reverse: false,
+            finished: false,
+            pending: Vec::new(),
+        };
+        sprite.pending = sprite.events_at(0);
+        sprite
+    }
+
+    pub fn has_clip(&self, name: &str) -> bool {
+        self.clips.iter().any(|clip| clip.name == name)
     }
 
-    pub fn update(&mut self) {
-        if self.last_update.elapsed() >= self.frame_duration {
-            self.current_frame = (self.current_frame + 1) % self.frames.len();
-            self.last_update = Instant::now();
+    // Cambia de clip y lo reinicia; si ya se esta reproduciendo no hace nada
+    pub fn play(&mut self, name: &str) {
+        if self.clips[self.current_clip].name == name {
+            return;
+        }
+        match self.clips.iter().position(|clip| clip.name == name) {
+            Some(index) => {
+                self.current_clip = index;
+                self.current_frame = 0;
+                self.elapsed = 0.0;
+                self.reverse = false;
+                self.finished = false;
+                self.pending = self.events_at(0);
+            }
+            None => println!("Unknown animation clip: {}", name),
         }
     }
-    
-    pub fn render(&self, buffer: &mut [u32], width: usize, height: usize) {
-        let frame = &self.frames[self.current_frame];
-        let (frame_width, frame_height) = frame.dimensions();
 
-        let x_offset = (width as u32 - frame_width) / 2;
-        let y_offset = height as u32 - frame_height;
+    // Avanza con el `dt` de la simulacion y devuelve los eventos de los
+    // cuadros a los que se entro.
+    pub fn update(&mut self, dt: f64) -> Vec<FrameEvent> {
+        let mut events = std::mem::take(&mut self.pending);
+        if self.finished {
+            return events;
+        }
+        self.elapsed += dt;
 
-        for y in 0..frame_height {
-            for x in 0..frame_width {
-                let px = frame.get_pixel(x, y);
-                let alpha = px[3];
-                let idx = ((y + y_offset) as usize * width + (x + x_offset) as usize) % (width * height);
+        let clip = &self.clips[self.current_clip];
+        loop {
+            let duration = clip.durations[self.current_frame];
+            if self.elapsed < duration {
+                break;
+            }
+            self.elapsed -= duration;
 
-                if alpha > 0 {
-                    buffer[idx] = px[0] as u32 | ((px[1] as u32) << 8) | ((px[2] as u32) << 16) | ((px[3] as u32) << 24);
+            let last = clip.frames.len() - 1;
+            match clip.mode {
+                PlayMode::Loop => self.current_frame = if self.current_frame == last { 0 } else { self.current_frame + 1 },
+                PlayMode::Once if self.current_frame == last => {
+                    self.finished = true;
+                    break;
+                }
+                PlayMode::Once => self.current_frame += 1,
+                PlayMode::PingPong if last == 0 => {}
+                PlayMode::PingPong => {
+                    if self.reverse && self.current_frame == 0 || !self.reverse && self.current_frame == last {
+                        self.reverse = !self.reverse;
+                    }
+                    self.current_frame = if self.reverse { self.current_frame - 1 } else { self.current_frame + 1 };
                 }
             }
+
+            events.extend(self.events_at(self.current_frame));
         }
+        events
+    }
+
+    // Eventos del clip actual en el cuadro `frame`
+    fn events_at(&self, frame: usize) -> Vec<FrameEvent> {
+        self.clips[self.current_clip].events.iter()
+            .filter(|(event_frame, _)| *event_frame == frame)
+            .map(|(_, name)| FrameEvent { name: name.clone() })
+            .collect()
+    }
+
+    pub fn frame(&self) -> &RgbaImage {
+        self.frame_facing(0)
+    }
+
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    pub fn directions(&self) -> usize {
+        self.directions
+    }
+
+    // Cuadro actual visto desde el angulo `direction` (0..directions)
+    pub fn frame_facing(&self, direction: usize) -> &RgbaImage {
+        let per_direction = self.frames.len() / self.directions;
+        let index = self.clips[self.current_clip].frames[self.current_frame];
+        &self.frames[(direction % self.directions) * per_direction + index]
+    }
+
+    pub fn render(&self, buffer: &mut [u32], width: usize, height: usize) {
+        composite::blit_placed(buffer, width, height, self.frame(), &self.placement);
+    }
+}
+
+// Imagenes que usa el atlas: la lista de `frames` o la hoja de `image`
+fn image_files(atlas: &Config) -> impl Iterator<Item = &str> {
+    let list = atlas.get("sheet", "frames").or_else(|| atlas.get("sheet", "image")).unwrap_or("");
+    split_list(list)
+}
+
+fn load_frames(assets: &Assets, atlas: &Config, atlas_file: &str) -> Result<Vec<RgbaImage>, GameError> {
+    if let Some(files) = atlas.get("sheet", "frames") {
+        return split_list(files)
+            .map(|file| assets.load_image(file).map(|img| img.to_rgba8()))
+            .collect();
+    }
+
+    let invalid = |e: &str| GameError::config(atlas_file, e);
+    let image_file = atlas.get("sheet", "image").ok_or_else(|| invalid("[sheet] needs 'image' or 'frames'"))?;
+    let sheet = assets.load_image(image_file)?.to_rgba8();
+    let size = |key: &str| -> Result<u32, GameError> {
+        let value = atlas.get_f64("sheet", key).map_err(|e| invalid(&e))?;
+        Ok(value.ok_or_else(|| invalid(&format!("[sheet] needs '{}'", key)))? as u32)
+    };
+    let (frame_width, frame_height) = (size("frame_width")?, size("frame_height")?);
+    if frame_width == 0 || frame_height == 0 {
+        return Err(invalid("frame size must be positive"));
+    }
+
+    let columns = sheet.width() / frame_width;
+    let rows = sheet.height() / frame_height;
+    let mut frames = Vec::with_capacity((columns * rows) as usize);
+    for row in 0..rows {
+        for column in 0..columns {
+            let frame = image::imageops::crop_imm(&sheet, column * frame_width, row * frame_height, frame_width, frame_height);
+            frames.push(frame.to_image());
+        }
+    }
+    Ok(frames)
+}
+
+fn parse_clip(atlas: &Config, section: &str, name: &str, frame_count: usize) -> Result<Clip, String> {
+    let frames = match atlas.get(section, "frames") {
+        Some(list) => split_list(list)
+            .map(|v| v.parse::<usize>().map_err(|_| format!("[{}] invalid frame '{}'", section, v)))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => (0..frame_count).collect(),
+    };
+    if frames.is_empty() {
+        return Err(format!("[{}] has no frames", section));
+    }
+    if let Some(&frame) = frames.iter().find(|&&f| f >= frame_count) {
+        return Err(format!("[{}] frame {} out of range (sheet has {})", section, frame, frame_count));
+    }
+
+    let durations: Vec<f64> = split_list(atlas.get(section, "duration").unwrap_or("100"))
+        .map(|v| v.parse::<f64>().map(|ms| ms / 1000.0).map_err(|_| format!("[{}] invalid duration '{}'", section, v)))
+        .collect::<Result<_, _>>()?;
+    let durations = match durations.len() {
+        1 => vec![durations[0]; frames.len()],
+        n if n == frames.len() => durations,
+        n => return Err(format!("[{}] has {} frames but {} durations", section, frames.len(), n)),
+    };
+    if durations.iter().any(|&d| d <= 0.0) {
+        return Err(format!("[{}] durations must be positive", section));
+    }
+
+    let mode = match atlas.get(section, "mode").unwrap_or("loop") {
+        "loop" => PlayMode::Loop,
+        "once" => PlayMode::Once,
+        "pingpong" => PlayMode::PingPong,
+        other => return Err(format!("[{}] unknown mode '{}'", section, other)),
+    };
+
+    let mut events = Vec::new();
+    for event in atlas.get_all(section, "event") {
+        let (frame, event_name) = event.split_once(':')
+            .ok_or_else(|| format!("[{}] event '{}' should be 'frame:name'", section, event))?;
+        let frame = frame.trim().parse::<usize>()
+            .map_err(|_| format!("[{}] invalid event frame '{}'", section, frame))?;
+        events.push((frame, event_name.trim().to_string()));
+    }
+
+    Ok(Clip { name: name.to_string(), frames, durations, mode, events })
+}
+
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',').map(str::trim).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sprite de cuatro cuadros de 1x1 con los clips del atlas dado
+    fn sprite(atlas: &str) -> AnimatedSprite {
+        let atlas = Config::parse(atlas).unwrap();
+        let clips = atlas.section_names()
+            .filter_map(|section| Some(parse_clip(&atlas, section, section.strip_prefix("clip.")?, 4).unwrap()))
+            .collect();
+        let frames = vec![RgbaImage::new(1, 1); 4];
+        AnimatedSprite::new(Placement::new(Anchor::Bottom), Vec::new(), frames, 1, clips)
+    }
+
+    // Cuadro del clip en el que queda despues de cada paso de `dt`
+    fn frames_after(sprite: &mut AnimatedSprite, steps: usize, dt: f64) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                sprite.update(dt);
+                sprite.current_frame
+            })
+            .collect()
+    }
+
+    fn names(events: Vec<FrameEvent>) -> Vec<String> {
+        events.into_iter().map(|event| event.name).collect()
+    }
+
+    #[test]
+    fn loop_wraps_to_the_first_frame() {
+        let mut sprite = sprite("[clip.walk]\nframes = 0, 1, 2\nduration = 250\nmode = loop\n");
+        assert_eq!(frames_after(&mut sprite, 5, 0.25), [1, 2, 0, 1, 2]);
+        // Un paso largo avanza varios cuadros a la vez
+        sprite.update(0.75);
+        assert_eq!(sprite.current_frame, 2);
+    }
+
+    #[test]
+    fn once_stops_on_the_last_frame() {
+        let mut sprite = sprite("[clip.attack]\nframes = 0, 1, 2\nduration = 250\nmode = once\n");
+        assert_eq!(frames_after(&mut sprite, 4, 0.25), [1, 2, 2, 2]);
+        assert!(sprite.finished);
+    }
+
+    #[test]
+    fn pingpong_bounces_at_both_ends() {
+        let mut sprite = sprite("[clip.idle]\nframes = 0, 1, 2\nduration = 250\nmode = pingpong\n");
+        assert_eq!(frames_after(&mut sprite, 6, 0.25), [1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn per_frame_durations_are_respected() {
+        let mut sprite = sprite("[clip.walk]\nframes = 0, 1\nduration = 250, 500\n");
+        assert_eq!(frames_after(&mut sprite, 4, 0.25), [1, 1, 0, 1]);
+    }
+
+    #[test]
+    fn events_fire_when_their_frame_is_entered() {
+        let mut sprite = sprite("[clip.walk]\nframes = 0, 1, 2\nduration = 250\nevent = 0:start\nevent = 2:step\n");
+        // El primer cuadro cuenta como entrado al empezar el clip
+        assert_eq!(names(sprite.update(0.0)), ["start"]);
+        assert!(sprite.update(0.0).is_empty());
+        assert!(sprite.update(0.25).is_empty());
+        assert_eq!(names(sprite.update(0.25)), ["step"]);
+        assert_eq!(names(sprite.update(0.25)), ["start"]);
+        assert_eq!(names(sprite.update(0.5)), ["step"]);
+    }
+
+    #[test]
+    fn play_fires_the_first_frame_event() {
+        let mut sprite = sprite("[clip.idle]\nframes = 0\n\n[clip.attack]\nframes = 0, 1\nduration = 250\nmode = once\nevent = 0:swing\n");
+        assert!(sprite.update(0.1).is_empty());
+        sprite.play("attack");
+        assert_eq!(names(sprite.update(0.0)), ["swing"]);
+        // Volver a pedir el clip que ya suena no lo reinicia
+        sprite.play("attack");
+        assert!(sprite.update(0.0).is_empty());
     }
 }
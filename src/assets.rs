@@ -0,0 +1,123 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use image::DynamicImage;
+
+use crate::error::GameError;
+use crate::pack::Pack;
+
+pub const BASE_DIR: &str = "assets";
+pub const PACKS_DIR: &str = "packs";
+
+// Cargador central de recursos. Los nombres son relativos a `assets/`
+// ("maze.txt", "walltexture1.jpg"); los paquetes activos se revisan primero,
+// de mayor a menor prioridad, y si ninguno tiene el archivo se usa la base.
+pub struct Assets {
+    base: String,
+    packs: Vec<Pack>,
+    conflicts: Vec<Conflict>,
+}
+
+// Dos o mas paquetes activos reemplazan el mismo archivo
+pub struct Conflict {
+    pub file: String,
+    pub winner: String,
+    pub overridden: Vec<String>,
+}
+
+impl Assets {
+    pub fn new(base: &str) -> Self {
+        Assets { base: base.to_string(), packs: Vec::new(), conflicts: Vec::new() }
+    }
+
+    // `ids` son los nombres de carpeta/zip dentro de `packs/`, en el orden en
+    // que se pidieron; a igual prioridad gana el que aparece primero.
+    pub fn with_packs(base: &str, packs_dir: &str, ids: &[String]) -> Result<Self, String> {
+        let mut available = Pack::discover(packs_dir);
+        let mut packs = Vec::new();
+        for id in ids {
+            let index = available.iter().position(|p| &p.id == id)
+                .ok_or_else(|| format!("Pack '{}' not found in '{}'", id, packs_dir))?;
+            packs.push(available.remove(index));
+        }
+        packs.sort_by_key(|p| std::cmp::Reverse(p.priority));
+
+        let mut assets = Assets { base: base.to_string(), packs, conflicts: Vec::new() };
+        assets.conflicts = assets.find_conflicts();
+        for conflict in &assets.conflicts {
+            println!(
+                "Pack conflict: '{}' from '{}' overrides {}",
+                conflict.file, conflict.winner, conflict.overridden.join(", ")
+            );
+        }
+        Ok(assets)
+    }
+
+    pub fn active_packs(&self) -> impl Iterator<Item = &Pack> {
+        self.packs.iter()
+    }
+
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.packs.iter().any(|pack| pack.contains(name)) || Path::new(&self.base).join(name).is_file()
+    }
+
+    // Archivo en disco del que sale `name`, para vigilar si cambia. Si ningun
+    // paquete lo tiene es la ruta en la base, aunque todavia no exista; si lo
+    // trae un .zip no hay archivo que vigilar.
+    pub fn path(&self, name: &str) -> Option<PathBuf> {
+        match self.packs.iter().find(|pack| pack.contains(name)) {
+            Some(pack) => pack.path(name),
+            None => Some(Path::new(&self.base).join(name)),
+        }
+    }
+
+    pub fn read(&self, name: &str) -> Result<Vec<u8>, GameError> {
+        for pack in &self.packs {
+            // Un .zip danado no se puede leer aunque el archivo este
+            let bytes = pack.read(name).map_err(|e| GameError::Io {
+                file: name.to_string(),
+                error: io::Error::new(io::ErrorKind::InvalidData, e),
+            })?;
+            if let Some(bytes) = bytes {
+                return Ok(bytes);
+            }
+        }
+        let path = Path::new(&self.base).join(name);
+        fs::read(&path).map_err(|error| GameError::Io { file: path.display().to_string(), error })
+    }
+
+    pub fn read_to_string(&self, name: &str) -> Result<String, GameError> {
+        let bytes = self.read(name)?;
+        String::from_utf8(bytes).map_err(|_| GameError::Io {
+            file: name.to_string(),
+            error: io::Error::new(io::ErrorKind::InvalidData, "not valid UTF-8"),
+        })
+    }
+
+    pub fn load_image(&self, name: &str) -> Result<DynamicImage, GameError> {
+        let bytes = self.read(name)?;
+        image::load_from_memory(&bytes)
+            .map_err(|e| GameError::Image { file: name.to_string(), message: e.to_string() })
+    }
+
+    fn find_conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts: Vec<Conflict> = Vec::new();
+        for pack in &self.packs {
+            let mut files = pack.files();
+            files.sort();
+            for file in files {
+                match conflicts.iter_mut().find(|c| c.file == file) {
+                    Some(conflict) => conflict.overridden.push(pack.id.clone()),
+                    None => conflicts.push(Conflict { file, winner: pack.id.clone(), overridden: Vec::new() }),
+                }
+            }
+        }
+        conflicts.retain(|c| !c.overridden.is_empty());
+        conflicts
+    }
+}
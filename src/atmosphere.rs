@@ -0,0 +1,124 @@
+use crate::config::Config;
+
+// Ambiente de un nivel. Se lee de la seccion [atmosphere] del archivo que
+// acompana al mapa (maze.txt -> maze.cfg):
+//
+//   [atmosphere]
+//   fog = exponential             # linear | exponential | exponential2
+//   fog_color = "#000000"
+//   fog_density = 0.2             # exponential y exponential2
+//   fog_start = 2.0               # linear: donde empieza...
+//   fog_end = 12.0                # ...y donde ya no se ve nada
+//   fog_floor = false             # aplicar niebla tambien al piso
+//   fog_sprites = false           # ...y a los sprites
+//   ambient = 1.0                 # luz ambiente (0 = negro, 1 = sin cambio);
+//                                 # las luces del nivel se suman a esta
+//   sky_top = "#20586d"
+//   sky_bottom = "#9ea2ac"
+//   floor_near = "#051744"
+//   floor_far = "#0d798f"
+//
+// Sin archivo se usan los valores de arriba.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Fog {
+    Linear { start: f64, end: f64 },
+    Exponential { density: f64 },
+    Exponential2 { density: f64 },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Atmosphere {
+    pub fog: Fog,
+    pub fog_color: u32,
+    pub fog_floor: bool,
+    pub fog_sprites: bool,
+    pub ambient: f64,
+    pub sky_top: u32,
+    pub sky_bottom: u32,
+    pub floor_near: u32,
+    pub floor_far: u32,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Atmosphere::new()
+    }
+}
+
+impl Atmosphere {
+    pub fn new() -> Self {
+        Atmosphere {
+            fog: Fog::Exponential { density: 0.2 },
+            fog_color: 0x000000,
+            fog_floor: false,
+            fog_sprites: false,
+            ambient: 1.0,
+            sky_top: 0x20586d,
+            sky_bottom: 0x9ea2ac,
+            floor_near: 0x051744,
+            floor_far: 0x0d798f,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let mut atmosphere = Atmosphere::new();
+        let section = "atmosphere";
+
+        let density = config.get_f64(section, "fog_density")?;
+        atmosphere.fog = match config.get(section, "fog").unwrap_or("exponential") {
+            "linear" => {
+                let start = config.get_f64(section, "fog_start")?.unwrap_or(2.0);
+                let end = config.get_f64(section, "fog_end")?.unwrap_or(12.0);
+                if end <= start {
+                    return Err(format!("[{}] fog_end must be greater than fog_start", section));
+                }
+                Fog::Linear { start, end }
+            }
+            "exponential" => Fog::Exponential { density: density.unwrap_or(0.2) },
+            "exponential2" => Fog::Exponential2 { density: density.unwrap_or(0.32) },
+            other => return Err(format!("[{}] unknown fog model '{}'", section, other)),
+        };
+
+        let color = |key: &str, default: u32| config.get_color(section, key).map(|c| c.unwrap_or(default));
+        atmosphere.fog_color = color("fog_color", atmosphere.fog_color)?;
+        atmosphere.sky_top = color("sky_top", atmosphere.sky_top)?;
+        atmosphere.sky_bottom = color("sky_bottom", atmosphere.sky_bottom)?;
+        atmosphere.floor_near = color("floor_near", atmosphere.floor_near)?;
+        atmosphere.floor_far = color("floor_far", atmosphere.floor_far)?;
+
+        atmosphere.fog_floor = config.get_bool(section, "fog_floor")?.unwrap_or(atmosphere.fog_floor);
+        atmosphere.fog_sprites = config.get_bool(section, "fog_sprites")?.unwrap_or(atmosphere.fog_sprites);
+        atmosphere.ambient = config.get_f64(section, "ambient")?.unwrap_or(atmosphere.ambient).clamp(0.0, 1.0);
+        Ok(atmosphere)
+    }
+
+    // Cuanto se ve a la distancia `d` (1 = nada de niebla, 0 = solo niebla)
+    pub fn visibility(&self, d: f64) -> f64 {
+        let v = match self.fog {
+            Fog::Linear { start, end } => (end - d) / (end - start),
+            Fog::Exponential { density } => (-density * d).exp(),
+            Fog::Exponential2 { density } => (-(density * d).powi(2)).exp(),
+        };
+        v.clamp(0.0, 1.0)
+    }
+
+    // Ilumina un color 0x00RRGGBB con la luz ambiente mas `light` (RGB, 1 =
+    // luz plena), sin niebla
+    pub fn light(&self, color: u32, light: [f64; 3]) -> u32 {
+        self.mix(color, light, 1.0)
+    }
+
+    // Igual que `light`, y despues aplica la niebla a `distance`
+    pub fn shade(&self, color: u32, light: [f64; 3], distance: f64) -> u32 {
+        self.mix(color, light, self.visibility(distance))
+    }
+
+    fn mix(&self, color: u32, light: [f64; 3], visibility: f64) -> u32 {
+        let channel = |shift: u32, light: f64| {
+            let c = ((color >> shift) & 0xFF) as f64 * (self.ambient + light);
+            let f = ((self.fog_color >> shift) & 0xFF) as f64;
+            ((f + (c - f) * visibility) as u32).min(255) << shift
+        };
+        channel(16, light[0]) | channel(8, light[1]) | channel(0, light[2])
+    }
+}
@@ -1,49 +1,421 @@
-use rodio::{Decoder, OutputStream, Sink};
-use std::fs::File;
-use std::io::BufReader;
+use std::sync::Arc;
+use crate::assets::Assets;
+use crate::audio_backend::{AudioBackend, VoiceId};
+use crate::error::GameError;
+use crate::mixer::{self, Bus, Cue, MixerSettings, CUES};
+use crate::player::Player;
+use crate::spatial::{self, Gains};
+use crate::tension::TensionLevel;
+
+// Voces simultaneas como maximo para efectos de sonido
+const MAX_VOICES: usize = 16;
+const CROSSFADE_SECONDS: f32 = 1.0;
+// Tiempo que tarda la musica en recuperarse despues de un ducking
+const DUCK_RELEASE_SECONDS: f32 = 1.5;
+// Volumen de la musica con tension 0; con tension 1 suena completa
+const INTENSITY_FLOOR: f32 = 0.6;
 
 pub struct AudioManager {
-    _stream: OutputStream,
-    stream_handle: rodio::OutputStreamHandle,
-    sink: Sink,
+    backend: Box<dyn AudioBackend>,
+    settings: MixerSettings,
+    music: Option<MusicTrack>,
+    fading_out: Vec<MusicTrack>,
+    voices: Vec<Voice>,
+    footsteps: Option<VoiceId>,
+    duck_level: f32,
+    intensity: f32,
+    tension_level: TensionLevel,
+    // Cues cuyo archivo existe (los stems opcionales pueden faltar)
+    available: Vec<&'static str>,
+}
+
+// Pista del bus de musica; `fade` va de 0 a 1 durante el crossfade
+struct MusicTrack {
+    id: VoiceId,
+    cue: &'static Cue,
+    fade: f32,
+    stems: Vec<Stem>,
+}
+
+// Capa que se mezcla sobre una pista segun el nivel de tension
+struct Stem {
+    id: VoiceId,
+    cue: &'static Cue,
+    level: TensionLevel,
+    fade: f32,
+}
+
+// Un efecto de sonido en reproduccion; si tiene posicion se panea y atenua
+// cada cuadro respecto al jugador.
+struct Voice {
+    id: VoiceId,
+    gains: Arc<Gains>,
+    position: Option<(f64, f64)>,
+    cue: &'static Cue,
 }
 
 impl AudioManager {
-    pub fn new() -> Self {
-        let (stream, stream_handle) = OutputStream::try_default().unwrap();
-        let sink = Sink::try_new(&stream_handle).unwrap();
-        AudioManager { _stream: stream, stream_handle, sink }
+    pub fn new(mut backend: Box<dyn AudioBackend>, assets: &Assets, settings: MixerSettings) -> Result<Self, GameError> {
+        backend.load_sounds(assets)?;
+        Ok(AudioManager {
+            backend,
+            settings,
+            music: None,
+            fading_out: Vec::new(),
+            voices: Vec::new(),
+            footsteps: None,
+            duck_level: 1.0,
+            intensity: INTENSITY_FLOOR,
+            tension_level: TensionLevel::Calm,
+            available: available_cues(assets),
+        })
+    }
+
+    // Cambia los sonidos (p. ej. al activar paquetes) deteniendo todo lo que suena
+    pub fn reload(&mut self, assets: &Assets) -> Result<(), GameError> {
+        self.stop_sfx();
+        if let Some(footsteps) = self.footsteps.take() {
+            self.backend.stop(footsteps);
+        }
+        for track in self.music.take().into_iter().chain(self.fading_out.drain(..)) {
+            self.backend.stop(track.id);
+            for stem in track.stems {
+                self.backend.stop(stem.id);
+            }
+        }
+        self.backend.load_sounds(assets)?;
+        self.available = available_cues(assets);
+        Ok(())
     }
 
-    pub fn play_background_music(&self, file_path: &str) {
-        self.sink.stop(); 
-        let file = BufReader::new(File::open(file_path).unwrap());
-        let source = Decoder::new(file).unwrap();
-        self.sink.append(source);
-        self.sink.set_volume(0.5);
-        self.sink.play();
+    pub fn is_playing(&self) -> bool {
+        self.music.as_ref().is_some_and(|track| !self.backend.is_finished(track.id))
     }
 
-    pub fn play_victory(&self) {
-        self.sink.stop(); 
-        let file = BufReader::new(File::open("assets/victory.mp3").unwrap());
-        let source = Decoder::new(file).unwrap();
-        self.sink.append(source);
-        self.sink.set_volume(0.5);
-        self.sink.play();
+    pub fn settings(&self) -> MixerSettings {
+        self.settings
     }
 
-    pub fn play_game_over(&self) {
-        self.sink.stop(); 
-        let file = BufReader::new(File::open("assets/gameover1.mp3").unwrap());
-        let source = Decoder::new(file).unwrap();
-        self.sink.append(source);
-        self.sink.set_volume(0.5);
-        self.sink.play();
+    pub fn set_settings(&mut self, settings: MixerSettings) {
+        self.settings = settings;
+        self.apply_volumes();
     }
 
-    pub fn is_playing(&self) -> bool {
-        !self.sink.empty()
+    // La tension sube el volumen de la musica y decide que stems suenan
+    pub fn set_tension(&mut self, value: f32, level: TensionLevel) {
+        self.intensity = INTENSITY_FLOOR + (1.0 - INTENSITY_FLOOR) * value.clamp(0.0, 1.0);
+        self.tension_level = level;
+    }
+
+    // Dispara un cue sin posicion (musica, stingers, UI, efectos del jugador)
+    pub fn play_cue(&mut self, name: &str) {
+        let Some(cue) = mixer::find_cue(name) else {
+            println!("Unknown audio cue: {}", name);
+            return;
+        };
+        if cue.bus == Bus::Music {
+            self.crossfade_to(cue);
+        } else {
+            self.play_voice(cue, None, (1.0, 1.0));
+        }
+    }
+
+    // Dispara un cue que se escucha desde un punto del mapa
+    pub fn play_cue_at(&mut self, name: &str, position: (f64, f64), player: &Player) {
+        let Some(cue) = mixer::find_cue(name) else {
+            println!("Unknown audio cue: {}", name);
+            return;
+        };
+        let gains = spatial::spatialize(player, position.0, position.1);
+        self.play_voice(cue, Some(position), gains);
+    }
+
+    // Los pasos son una pista larga que se pausa cuando el jugador se detiene
+    pub fn set_footsteps(&mut self, walking: bool) {
+        if let Some(id) = self.footsteps {
+            if self.backend.is_finished(id) {
+                self.backend.stop(id);
+                self.footsteps = None;
+            }
+        }
+        if walking && self.footsteps.is_none() {
+            if let Some(cue) = mixer::find_cue("sfx.footsteps") {
+                self.footsteps = self.backend.start(cue, Gains::new(1.0, 1.0));
+                self.apply_volumes();
+            }
+        }
+        if let Some(id) = self.footsteps {
+            self.backend.set_paused(id, !walking);
+        }
+    }
+
+    pub fn stop_sfx(&mut self) {
+        for voice in self.voices.drain(..) {
+            self.backend.stop(voice.id);
+        }
+        if let Some(id) = self.footsteps {
+            self.backend.set_paused(id, true);
+        }
+    }
+
+    // Avanza crossfades y ducking, reinicia pistas en loop y recalcula el
+    // paneo de las voces posicionales con la pose actual del jugador.
+    pub fn update(&mut self, dt: f64, player: &Player) {
+        let dt = dt as f32;
+        let step = dt / CROSSFADE_SECONDS;
+
+        if let Some(track) = &mut self.music {
+            track.fade = (track.fade + step).min(1.0);
+            for stem in &mut track.stems {
+                let target = if self.tension_level >= stem.level { 1.0 } else { 0.0 };
+                stem.fade += (target - stem.fade).clamp(-step, step);
+            }
+            // Los stems vuelven a empezar junto con la pista para no desfasarse
+            if track.cue.looping && self.backend.is_finished(track.id) {
+                self.backend.stop(track.id);
+                for stem in &track.stems {
+                    self.backend.stop(stem.id);
+                }
+                match self.backend.start(track.cue, Gains::new(1.0, 1.0)) {
+                    Some(id) => {
+                        track.id = id;
+                        for stem in &mut track.stems {
+                            if let Some(id) = self.backend.start(stem.cue, Gains::new(1.0, 1.0)) {
+                                stem.id = id;
+                            }
+                        }
+                    }
+                    None => self.music = None,
+                }
+            }
+        }
+        for track in &mut self.fading_out {
+            track.fade -= step;
+            if track.fade <= 0.0 {
+                self.backend.stop(track.id);
+                for stem in &track.stems {
+                    self.backend.stop(stem.id);
+                }
+            }
+        }
+        self.fading_out.retain(|track| track.fade > 0.0);
+
+        self.remove_finished_voices();
+        for voice in &self.voices {
+            if let Some((x, y)) = voice.position {
+                voice.gains.set(spatial::spatialize(player, x, y));
+            }
+        }
+
+        // Manda el ducking mas fuerte de las voces activas; al terminar la
+        // musica se recupera gradualmente.
+        let target = self.voices.iter().map(|voice| voice.cue.duck_music).fold(1.0, f32::min);
+        self.duck_level = if target < self.duck_level {
+            target
+        } else {
+            (self.duck_level + dt / DUCK_RELEASE_SECONDS).min(target)
+        };
+
+        self.apply_volumes();
+    }
+
+    fn crossfade_to(&mut self, cue: &'static Cue) {
+        let Some(id) = self.backend.start(cue, Gains::new(1.0, 1.0)) else {
+            return;
+        };
+        // Los stems arrancan junto con la pista para quedar sincronizados
+        let mut stems = Vec::new();
+        for stem_cue in CUES.iter().filter(|c| self.available.contains(&c.name)) {
+            if let Some((parent, level)) = stem_cue.stem {
+                if parent == cue.name {
+                    if let Some(stem_id) = self.backend.start(stem_cue, Gains::new(1.0, 1.0)) {
+                        stems.push(Stem { id: stem_id, cue: stem_cue, level, fade: 0.0 });
+                    }
+                }
+            }
+        }
+
+        if let Some(previous) = self.music.take() {
+            self.fading_out.push(previous);
+        }
+        self.music = Some(MusicTrack { id, cue, fade: 0.0, stems });
+        self.apply_volumes();
+    }
+
+    fn play_voice(&mut self, cue: &'static Cue, position: Option<(f64, f64)>, gains: (f32, f32)) {
+        // Un cue opcional sin archivo no suena
+        if !self.available.contains(&cue.name) {
+            return;
+        }
+        self.remove_finished_voices();
+        if self.voices.len() >= MAX_VOICES {
+            return;
+        }
+        let gains = Gains::new(gains.0, gains.1);
+        let Some(id) = self.backend.start(cue, gains.clone()) else {
+            return;
+        };
+        self.voices.push(Voice { id, gains, position, cue });
+        self.apply_volumes();
+    }
+
+    fn remove_finished_voices(&mut self) {
+        let backend = &mut self.backend;
+        self.voices.retain(|voice| {
+            let finished = backend.is_finished(voice.id);
+            if finished {
+                backend.stop(voice.id);
+            }
+            !finished
+        });
+    }
+
+    fn apply_volumes(&mut self) {
+        let music_gain = self.settings.gain(Bus::Music) * self.duck_level * self.intensity;
+        for track in self.music.iter().chain(self.fading_out.iter()) {
+            let fade = track.fade.max(0.0);
+            self.backend.set_volume(track.id, music_gain * fade);
+            for stem in &track.stems {
+                self.backend.set_volume(stem.id, music_gain * fade * stem.fade);
+            }
+        }
+        for voice in &self.voices {
+            self.backend.set_volume(voice.id, self.settings.gain(voice.cue.bus));
+        }
+        if let Some(id) = self.footsteps {
+            self.backend.set_volume(id, self.settings.gain(Bus::Sfx));
+        }
     }
 }
 
+fn available_cues(assets: &Assets) -> Vec<&'static str> {
+    CUES.iter()
+        .filter(|cue| !cue.optional || cue.synth.is_some() || assets.exists(cue.file))
+        .map(|cue| cue.name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::BASE_DIR;
+    use crate::config::Config;
+    use crate::audio_backend::{NullBackend, RecordingBackend};
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    #[test]
+    fn victory_sting_is_logged_by_the_recording_backend() {
+        let (backend, log) = RecordingBackend::new(Box::new(NullBackend::new()));
+        let mut audio = AudioManager::new(Box::new(backend), &Assets::new(BASE_DIR), MixerSettings::new()).unwrap();
+        audio.play_cue("music.chase");
+        audio.play_cue("sting.victory");
+
+        let cues: Vec<&str> = log.events().iter().map(|&(_, name)| name).collect();
+        assert_eq!(cues, ["music.chase", "music.stem.alert", "music.stem.chase", "sting.victory"]);
+    }
+
+    #[test]
+    fn stems_play_with_the_base_assets() {
+        let (backend, log) = RecordingBackend::new(Box::new(NullBackend::new()));
+        let mut audio = AudioManager::new(Box::new(backend), &Assets::new(BASE_DIR), MixerSettings::new()).unwrap();
+        audio.play_cue("music.chase");
+
+        let cues: Vec<&str> = log.events().iter().map(|&(_, name)| name).collect();
+        assert!(cues.contains(&"music.stem.alert"));
+        assert!(cues.contains(&"music.stem.chase"));
+    }
+
+    // Como `NullBackend`, pero la pista principal termina cuando el test lo pide
+    struct EndingBackend {
+        inner: NullBackend,
+        cues: HashMap<VoiceId, &'static str>,
+        ended: Rc<Cell<bool>>,
+    }
+
+    impl AudioBackend for EndingBackend {
+        fn load_sounds(&mut self, assets: &Assets) -> Result<(), GameError> {
+            self.inner.load_sounds(assets)
+        }
+
+        fn start(&mut self, cue: &'static Cue, gains: Arc<Gains>) -> Option<VoiceId> {
+            let id = self.inner.start(cue, gains)?;
+            self.cues.insert(id, cue.name);
+            Some(id)
+        }
+
+        fn set_volume(&mut self, id: VoiceId, volume: f32) {
+            self.inner.set_volume(id, volume);
+        }
+
+        fn set_paused(&mut self, id: VoiceId, paused: bool) {
+            self.inner.set_paused(id, paused);
+        }
+
+        fn is_finished(&self, id: VoiceId) -> bool {
+            (self.ended.get() && self.cues.get(&id) == Some(&"music.chase")) || self.inner.is_finished(id)
+        }
+
+        fn stop(&mut self, id: VoiceId) {
+            self.inner.stop(id);
+        }
+    }
+
+    #[test]
+    fn stems_restart_together_with_the_main_track() {
+        let ended = Rc::new(Cell::new(false));
+        let inner = EndingBackend { inner: NullBackend::new(), cues: HashMap::new(), ended: ended.clone() };
+        let (backend, log) = RecordingBackend::new(Box::new(inner));
+        let mut audio = AudioManager::new(Box::new(backend), &Assets::new(BASE_DIR), MixerSettings::new()).unwrap();
+        let player = Player::at(1.5, 1.5);
+        audio.play_cue("music.chase");
+        audio.update(0.1, &player);
+        assert_eq!(log.events().len(), 3);
+
+        ended.set(true);
+        audio.update(0.1, &player);
+        ended.set(false);
+        audio.update(0.1, &player);
+
+        let cues: Vec<&str> = log.events().iter().map(|&(_, name)| name).collect();
+        let round = ["music.chase", "music.stem.alert", "music.stem.chase"];
+        assert_eq!(cues, [round, round].concat());
+    }
+
+    #[test]
+    fn stems_fade_in_with_the_tension_level() {
+        let mut audio = AudioManager::new(Box::new(NullBackend::new()), &Assets::new(BASE_DIR), MixerSettings::new()).unwrap();
+        let player = Player::at(1.5, 1.5);
+        audio.play_cue("music.chase");
+        let mut fades_at = |level| {
+            audio.set_tension(0.5, level);
+            audio.update(CROSSFADE_SECONDS as f64, &player);
+            let track = audio.music.as_ref().unwrap();
+            track.stems.iter().map(|stem| (stem.cue.name, stem.fade)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(fades_at(TensionLevel::Calm), [("music.stem.alert", 0.0), ("music.stem.chase", 0.0)]);
+        assert_eq!(fades_at(TensionLevel::Alert), [("music.stem.alert", 1.0), ("music.stem.chase", 0.0)]);
+        assert_eq!(fades_at(TensionLevel::Chase), [("music.stem.alert", 1.0), ("music.stem.chase", 1.0)]);
+        assert_eq!(fades_at(TensionLevel::Calm), [("music.stem.alert", 0.0), ("music.stem.chase", 0.0)]);
+    }
+
+    #[test]
+    fn ui_cues_use_the_configurable_ui_bus() {
+        let config = Config::parse("[audio]\nui_volume = 0.3\nui_muted = true\n").unwrap();
+        let settings = MixerSettings::from_config(&config).unwrap();
+        assert_eq!(settings.volume(Bus::Ui), 0.3);
+        assert_eq!(settings.gain(Bus::Ui), 0.0);
+        assert_eq!(settings.gain(Bus::Sfx), 0.8);
+
+        let (backend, log) = RecordingBackend::new(Box::new(NullBackend::new()));
+        let mut audio = AudioManager::new(Box::new(backend), &Assets::new(BASE_DIR), settings).unwrap();
+        for name in ["ui.move", "ui.select", "ui.console"] {
+            assert_eq!(mixer::find_cue(name).unwrap().bus, Bus::Ui);
+            audio.play_cue(name);
+        }
+        let cues: Vec<&str> = log.events().iter().map(|&(_, name)| name).collect();
+        assert_eq!(cues, ["ui.move", "ui.select", "ui.console"]);
+    }
+}
@@ -0,0 +1,221 @@
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::assets::Assets;
+use crate::error::GameError;
+use crate::mixer::{Cue, CUES};
+use crate::spatial::{Gains, Panned};
+
+pub type VoiceId = u32;
+
+// Lo minimo que el mezclador necesita de un dispositivo de audio. El estado
+// (volumenes, crossfades, paneo) vive en `AudioManager`; el backend solo
+// reproduce.
+pub trait AudioBackend {
+    fn load_sounds(&mut self, assets: &Assets) -> Result<(), GameError>;
+    fn start(&mut self, cue: &'static Cue, gains: Arc<Gains>) -> Option<VoiceId>;
+    fn set_volume(&mut self, id: VoiceId, volume: f32);
+    fn set_paused(&mut self, id: VoiceId, paused: bool);
+    fn is_finished(&self, id: VoiceId) -> bool;
+    fn stop(&mut self, id: VoiceId);
+}
+
+// Usa rodio si hay dispositivo de audio; si no, sigue sin sonido
+pub fn default_backend() -> Box<dyn AudioBackend> {
+    match RodioBackend::new() {
+        Ok(backend) => Box::new(backend),
+        Err(e) => {
+            println!("Warning: no audio device available ({}), continuing without sound", e);
+            Box::new(NullBackend::new())
+        }
+    }
+}
+
+pub struct RodioBackend {
+    _stream: OutputStream,
+    stream_handle: OutputStreamHandle,
+    sounds: HashMap<&'static str, Vec<u8>>,
+    sinks: HashMap<VoiceId, Sink>,
+    next_id: VoiceId,
+}
+
+impl RodioBackend {
+    pub fn new() -> Result<Self, String> {
+        let (stream, stream_handle) = OutputStream::try_default().map_err(|e| e.to_string())?;
+        Ok(RodioBackend { _stream: stream, stream_handle, sounds: HashMap::new(), sinks: HashMap::new(), next_id: 0 })
+    }
+}
+
+impl AudioBackend for RodioBackend {
+    // Decodifica cada archivo una vez al cargar para que un archivo danado
+    // falle aqui, nombrado, y no en silencio al dispararse el cue
+    fn load_sounds(&mut self, assets: &Assets) -> Result<(), GameError> {
+        let mut sounds = HashMap::new();
+        for cue in CUES.iter() {
+            if cue.optional && !assets.exists(cue.file) {
+                continue;
+            }
+            let bytes = assets.read(cue.file)?;
+            Decoder::new(Cursor::new(bytes.clone()))
+                .map_err(|e| GameError::Audio { file: cue.file.to_string(), message: e.to_string() })?;
+            sounds.insert(cue.file, bytes);
+        }
+        self.sounds = sounds;
+        Ok(())
+    }
+
+    fn start(&mut self, cue: &'static Cue, gains: Arc<Gains>) -> Option<VoiceId> {
+        let source: Box<dyn Source<Item = f32> + Send> = match (self.sounds.get(cue.file), cue.synth) {
+            (Some(bytes), _) => match Decoder::new(Cursor::new(bytes.clone())) {
+                Ok(source) => Box::new(source.convert_samples::<f32>()),
+                Err(e) => {
+                    println!("Failed to decode sound '{}': {}", cue.file, e);
+                    return None;
+                }
+            },
+            // Sin archivo suena la version generada; un loop generado no
+            // termina, asi que acompana a la pista principal sin cortes
+            (None, Some(synth)) if cue.looping => Box::new(synth.source().repeat_infinite()),
+            (None, Some(synth)) => Box::new(synth.source()),
+            (None, None) => return None,
+        };
+        let sink = Sink::try_new(&self.stream_handle).ok()?;
+        sink.set_volume(0.0);
+        sink.append(Panned::new(source, gains));
+
+        self.next_id += 1;
+        self.sinks.insert(self.next_id, sink);
+        Some(self.next_id)
+    }
+
+    fn set_volume(&mut self, id: VoiceId, volume: f32) {
+        if let Some(sink) = self.sinks.get(&id) {
+            sink.set_volume(volume);
+        }
+    }
+
+    fn set_paused(&mut self, id: VoiceId, paused: bool) {
+        if let Some(sink) = self.sinks.get(&id) {
+            if paused {
+                sink.pause();
+            } else {
+                sink.play();
+            }
+        }
+    }
+
+    fn is_finished(&self, id: VoiceId) -> bool {
+        self.sinks.get(&id).is_none_or(|sink| sink.empty())
+    }
+
+    fn stop(&mut self, id: VoiceId) {
+        self.sinks.remove(&id);
+    }
+}
+
+// Sin dispositivo de audio: nada suena. Los cues que no hacen loop terminan
+// de inmediato para que el mezclador no acumule voces.
+pub struct NullBackend {
+    voices: HashMap<VoiceId, &'static Cue>,
+    next_id: VoiceId,
+}
+
+impl Default for NullBackend {
+    fn default() -> Self {
+        NullBackend::new()
+    }
+}
+
+impl NullBackend {
+    pub fn new() -> Self {
+        NullBackend { voices: HashMap::new(), next_id: 0 }
+    }
+}
+
+impl AudioBackend for NullBackend {
+    fn load_sounds(&mut self, _assets: &Assets) -> Result<(), GameError> {
+        Ok(())
+    }
+
+    fn start(&mut self, cue: &'static Cue, _gains: Arc<Gains>) -> Option<VoiceId> {
+        self.next_id += 1;
+        self.voices.insert(self.next_id, cue);
+        Some(self.next_id)
+    }
+
+    fn set_volume(&mut self, _id: VoiceId, _volume: f32) {}
+
+    fn set_paused(&mut self, _id: VoiceId, _paused: bool) {}
+
+    fn is_finished(&self, id: VoiceId) -> bool {
+        self.voices.get(&id).is_none_or(|cue| !cue.looping)
+    }
+
+    fn stop(&mut self, id: VoiceId) {
+        self.voices.remove(&id);
+    }
+}
+
+// Registro de cues compartido con quien creo el `RecordingBackend`
+#[derive(Clone)]
+pub struct CueLog {
+    events: Arc<Mutex<Vec<(Duration, &'static str)>>>,
+}
+
+impl CueLog {
+    pub fn events(&self) -> Vec<(Duration, &'static str)> {
+        self.events.lock().unwrap().clone()
+    }
+
+    pub fn save(&self, filename: &str) -> Result<(), String> {
+        let text: String = self.events().iter()
+            .map(|(time, name)| format!("{:.3} {}\n", time.as_secs_f64(), name))
+            .collect();
+        fs::write(filename, text).map_err(|e| format!("Failed to write audio log '{}': {}", filename, e))
+    }
+}
+
+// Envuelve otro backend y anota cada cue que se dispara y cuando
+pub struct RecordingBackend {
+    inner: Box<dyn AudioBackend>,
+    log: CueLog,
+    started: Instant,
+}
+
+impl RecordingBackend {
+    pub fn new(inner: Box<dyn AudioBackend>) -> (Self, CueLog) {
+        let log = CueLog { events: Arc::new(Mutex::new(Vec::new())) };
+        (RecordingBackend { inner, log: log.clone(), started: Instant::now() }, log)
+    }
+}
+
+impl AudioBackend for RecordingBackend {
+    fn load_sounds(&mut self, assets: &Assets) -> Result<(), GameError> {
+        self.inner.load_sounds(assets)
+    }
+
+    fn start(&mut self, cue: &'static Cue, gains: Arc<Gains>) -> Option<VoiceId> {
+        self.log.events.lock().unwrap().push((self.started.elapsed(), cue.name));
+        self.inner.start(cue, gains)
+    }
+
+    fn set_volume(&mut self, id: VoiceId, volume: f32) {
+        self.inner.set_volume(id, volume);
+    }
+
+    fn set_paused(&mut self, id: VoiceId, paused: bool) {
+        self.inner.set_paused(id, paused);
+    }
+
+    fn is_finished(&self, id: VoiceId) -> bool {
+        self.inner.is_finished(id)
+    }
+
+    fn stop(&mut self, id: VoiceId) {
+        self.inner.stop(id);
+    }
+}
@@ -0,0 +1,49 @@
+use std::f64::consts::PI;
+use std::time::{Duration, Instant};
+
+use proyecto1::map::Map;
+use proyecto1::player::Player;
+use proyecto1::renderer::Renderer;
+
+// Mide `render_3d` con un hilo y con `threads` hilos desde varias vistas del
+// nivel, y comprueba que ambas salidas sean identicas bit a bit.
+pub fn run(map: &Map, renderer: Renderer, threads: usize, frames: usize) -> Result<(), String> {
+    let mut renderer = renderer;
+    let mut player = Player::new(map);
+    let poses: Vec<f64> = (0..8).map(|i| i as f64 * PI / 4.0).collect();
+
+    for &angle in &poses {
+        player.angle = angle;
+        renderer.threads = 1;
+        renderer.render_3d(map, &player);
+        let reference = (renderer.frame.clone(), renderer.depth.clone());
+        renderer.threads = threads;
+        renderer.render_3d(map, &player);
+
+        let colors_match = reference.0 == renderer.frame;
+        let depths_match = reference.1.iter().zip(&renderer.depth).all(|(a, b)| a.to_bits() == b.to_bits());
+        if !colors_match || !depths_match {
+            return Err(format!("Output with {} threads differs from single-threaded output at angle {:.2}", threads, angle));
+        }
+    }
+
+    let mut time = |threads: usize| {
+        renderer.threads = threads;
+        let start = Instant::now();
+        for frame in 0..frames {
+            player.angle = poses[frame % poses.len()];
+            renderer.render_3d(map, &player);
+        }
+        start.elapsed()
+    };
+    let single = time(1);
+    let multi = time(threads);
+
+    let per_frame = |total: Duration| total.as_secs_f64() * 1000.0 / frames.max(1) as f64;
+    println!("Output identical across {} views", poses.len());
+    println!("{} frames at {}x{}", frames, renderer.width, renderer.height);
+    println!("  1 thread:   {:8.2} ms/frame", per_frame(single));
+    println!("  {} threads: {:8.2} ms/frame", threads, per_frame(multi));
+    println!("  speed-up:   {:8.2}x", single.as_secs_f64() / multi.as_secs_f64().max(f64::EPSILON));
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::f64::consts::PI;
+
+use crate::config::Config;
+
+// Velocidad de caminata (celdas por segundo) a la que el balanceo llega a su altura maxima
+const WALK_SPEED: f64 = 2.0;
+// Pasos por celda recorrida (un ciclo del seno son dos pasos)
+const STEPS_PER_CELL: f64 = 1.6;
+// Altura del balanceo como fraccion del alto de la pantalla
+const BOB_HEIGHT: f64 = 0.012;
+// Desplazamiento maximo del temblor: vertical (fraccion de la pantalla) y giro (radianes)
+const SHAKE_LIFT: f64 = 0.04;
+const SHAKE_YAW: f64 = 0.03;
+// Cuanto temblor se pierde por segundo
+const SHAKE_DECAY: f64 = 1.5;
+
+// Efectos de la camara que no cambian donde esta el jugador: el balanceo al
+// caminar y el temblor. El juego los actualiza cada cuadro y el renderer los
+// suma al horizonte y al angulo de la vista.
+//
+//   [camera]
+//   head_bob = 1.0     # 0 desactiva el balanceo
+//   shake = 1.0        # 0 desactiva el temblor
+pub struct Camera {
+    pub head_bob: f64,
+    pub shake_scale: f64,
+    bob_phase: f64,
+    bob_amount: f64,
+    // "Trauma" entre 0 y 1; el temblor crece con su cuadrado
+    trauma: f64,
+    time: f64,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera::new()
+    }
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Camera { head_bob: 1.0, shake_scale: 1.0, bob_phase: 0.0, bob_amount: 0.0, trauma: 0.0, time: 0.0 }
+    }
+
+    // Solo lee los ajustes; el balanceo y el temblor en curso se mantienen
+    pub fn configure(&mut self, config: &Config) -> Result<(), String> {
+        let head_bob = config.get_f64("camera", "head_bob")?.unwrap_or(1.0);
+        let shake = config.get_f64("camera", "shake")?.unwrap_or(1.0);
+        if head_bob < 0.0 || shake < 0.0 {
+            return Err("[camera] head_bob and shake can't be negative".to_string());
+        }
+        (self.head_bob, self.shake_scale) = (head_bob, shake);
+        Ok(())
+    }
+
+    // `speed` es lo que avanzo el jugador en celdas por segundo
+    pub fn update(&mut self, speed: f64, dt: f64) {
+        self.time += dt;
+        self.bob_phase = (self.bob_phase + speed * dt * STEPS_PER_CELL * PI).rem_euclid(2.0 * PI);
+        // Al detenerse el balanceo se apaga de a poco en vez de cortarse
+        let target = (speed / WALK_SPEED).min(1.0);
+        self.bob_amount += (target - self.bob_amount) * (dt * 8.0).min(1.0);
+        self.trauma = (self.trauma - SHAKE_DECAY * dt).max(0.0);
+    }
+
+    // Sacude la camara; `amount` entre 0 y 1 se acumula hasta 1
+    pub fn shake(&mut self, amount: f64) {
+        self.trauma = (self.trauma + amount.max(0.0)).min(1.0);
+    }
+
+    // Temblor continuo de al menos `level` (p. ej. mientras un enemigo esta cerca)
+    pub fn rumble(&mut self, level: f64) {
+        self.trauma = self.trauma.max(level.clamp(0.0, 1.0));
+    }
+
+    pub fn trauma(&self) -> f64 {
+        self.trauma
+    }
+
+    // Cuanto baja el horizonte, como fraccion del alto de la pantalla
+    pub fn lift(&self) -> f64 {
+        let bob = self.bob_phase.sin().abs() * BOB_HEIGHT * self.bob_amount * self.head_bob;
+        let shake = self.shake_strength() * SHAKE_LIFT * (self.time * 43.0).sin();
+        bob + shake
+    }
+
+    // Giro extra de la vista en radianes
+    pub fn yaw(&self) -> f64 {
+        self.shake_strength() * SHAKE_YAW * (self.time * 37.0 + 1.3).sin()
+    }
+
+    fn shake_strength(&self) -> f64 {
+        self.trauma * self.trauma * self.shake_scale
+    }
+}
@@ -0,0 +1,265 @@
+use proyecto1::generate::{self, Algorithm};
+
+pub const USAGE: &str = "\
+usage: proyecto1 [play] [OPTIONS]
+       proyecto1 render --map FILE --pose X,Y,DEGREES --out FILE.png
+                        [--width W --height H] [--pack NAME]
+       proyecto1 generate [--algo NAME] --size WxH --out FILE [--seed N]
+       proyecto1 check-map FILE [--pack NAME]
+       proyecto1 replay FILE [OPTIONS]
+
+play options:
+  --map FILE          map inside assets/ (default maze.txt)
+  --seed N            play a maze generated from seed N instead of a map file
+  --width W --height H
+                      window size
+  --fps N             frame rate cap (default 15)
+  --skip-intro        start playing right away
+  --record FILE       save the inputs of the game to replay it later
+  --pack NAME         enable a resource pack from packs/ (repeatable)
+  --no-audio          don't open the audio device
+  --audio-log FILE    on exit, save which cues played and when
+  --threads N         render threads (overrides [video] threads)
+  --bench FRAMES      time the renderer with 1 and N threads and exit
+  --exec FILE         run console commands at startup (after autoexec.cfg)
+  --headless          run without a window or audio, as fast as possible
+  --script FILE       synthetic input for --headless (see src/platform.rs)
+  --capture DIR       with --headless, save every frame as a PNG in DIR
+
+generate algorithms: backtracker (default), prim";
+
+pub enum Command {
+    Play(PlayOptions),
+    Render(RenderOptions),
+    Generate(GenerateOptions),
+    CheckMap(String, Vec<String>),
+    Replay(String, PlayOptions),
+    Help,
+}
+
+pub struct PlayOptions {
+    pub map: Option<String>,
+    pub seed: Option<u64>,
+    pub size: Option<(usize, usize)>,
+    pub fps: Option<u32>,
+    pub skip_intro: bool,
+    pub record: Option<String>,
+    pub packs: Vec<String>,
+    pub no_audio: bool,
+    pub audio_log: Option<String>,
+    pub threads: Option<usize>,
+    pub bench: Option<usize>,
+    pub exec: Option<String>,
+    pub headless: bool,
+    pub script: Option<String>,
+    pub capture: Option<String>,
+}
+
+pub struct RenderOptions {
+    pub map: String,
+    pub pose: (f64, f64, f64),
+    pub out: String,
+    pub size: Option<(usize, usize)>,
+    pub packs: Vec<String>,
+}
+
+pub struct GenerateOptions {
+    pub algorithm: Algorithm,
+    pub size: (usize, usize),
+    pub out: String,
+    pub seed: u64,
+}
+
+// Los argumentos sin el nombre del programa. Sin subcomando se juega, asi
+// que las opciones de antes (`--pack`, `--no-audio`, ...) siguen sirviendo.
+pub fn parse(args: Vec<String>) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(String::as_str) {
+        None => return Ok(Command::Play(play_options(&mut args)?)),
+        Some(arg) if arg.starts_with("--") && arg != "--help" => return Ok(Command::Play(play_options(&mut args)?)),
+        _ => {}
+    }
+    let command = args.next().unwrap_or_default();
+    match command.as_str() {
+        "play" => Ok(Command::Play(play_options(&mut args)?)),
+        "render" => Ok(Command::Render(parse_render(&mut args)?)),
+        "generate" => Ok(Command::Generate(parse_generate(&mut args)?)),
+        "check-map" => {
+            let file = args.next().ok_or("check-map requires a map file")?;
+            let mut packs = Vec::new();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--pack" => packs.push(value(&arg, args.next())?),
+                    _ => return Err(format!("Unknown argument '{}'", arg)),
+                }
+            }
+            Ok(Command::CheckMap(file, packs))
+        }
+        "replay" => {
+            let file = args.next().ok_or("replay requires a replay file")?;
+            let options = parse_play(&mut args)?;
+            if options.record.is_some() || options.seed.is_some() || options.map.is_some() {
+                return Err("replay takes the map from the replay file".to_string());
+            }
+            Ok(Command::Replay(file, options))
+        }
+        "help" | "--help" => Ok(Command::Help),
+        other => Err(format!("Unknown command '{}'", other)),
+    }
+}
+
+// Una partida sin ventana necesita un script; en `replay` la entrada sale de la grabacion
+fn play_options(args: &mut impl Iterator<Item = String>) -> Result<PlayOptions, String> {
+    let options = parse_play(args)?;
+    if options.headless && options.script.is_none() {
+        return Err("--headless needs --script FILE to know what to press".to_string());
+    }
+    Ok(options)
+}
+
+fn parse_play(args: &mut impl Iterator<Item = String>) -> Result<PlayOptions, String> {
+    let mut options = PlayOptions {
+        map: None,
+        seed: None,
+        size: None,
+        fps: None,
+        skip_intro: false,
+        record: None,
+        packs: Vec::new(),
+        no_audio: false,
+        audio_log: None,
+        threads: None,
+        bench: None,
+        exec: None,
+        headless: false,
+        script: None,
+        capture: None,
+    };
+    let (mut width, mut height) = (None, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--map" => options.map = Some(value(&arg, args.next())?),
+            "--seed" => options.seed = Some(number(&arg, args.next())?),
+            "--width" => width = Some(count(&arg, args.next())?),
+            "--height" => height = Some(count(&arg, args.next())?),
+            "--fps" => options.fps = Some(count(&arg, args.next())? as u32),
+            "--skip-intro" => options.skip_intro = true,
+            "--record" => options.record = Some(value(&arg, args.next())?),
+            "--pack" => options.packs.push(value(&arg, args.next())?),
+            "--no-audio" => options.no_audio = true,
+            "--audio-log" => options.audio_log = Some(value(&arg, args.next())?),
+            "--threads" => options.threads = Some(count(&arg, args.next())?),
+            "--bench" => options.bench = Some(count(&arg, args.next())?),
+            "--exec" => options.exec = Some(value(&arg, args.next())?),
+            "--headless" => options.headless = true,
+            "--script" => options.script = Some(value(&arg, args.next())?),
+            "--capture" => options.capture = Some(value(&arg, args.next())?),
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+    if options.map.is_some() && options.seed.is_some() {
+        return Err("--map and --seed can't be used together".to_string());
+    }
+    if !options.headless && (options.script.is_some() || options.capture.is_some()) {
+        return Err("--script and --capture only work with --headless".to_string());
+    }
+    options.size = size_pair(width, height)?;
+    Ok(options)
+}
+
+fn parse_render(args: &mut impl Iterator<Item = String>) -> Result<RenderOptions, String> {
+    let (mut map, mut pose, mut out) = (None, None, None);
+    let (mut width, mut height) = (None, None);
+    let mut packs = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--map" => map = Some(value(&arg, args.next())?),
+            "--pose" => pose = Some(parse_pose(&value(&arg, args.next())?)?),
+            "--out" => out = Some(value(&arg, args.next())?),
+            "--width" => width = Some(count(&arg, args.next())?),
+            "--height" => height = Some(count(&arg, args.next())?),
+            "--pack" => packs.push(value(&arg, args.next())?),
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+    Ok(RenderOptions {
+        map: map.ok_or("render requires --map FILE")?,
+        pose: pose.ok_or("render requires --pose X,Y,DEGREES")?,
+        out: out.ok_or("render requires --out FILE.png")?,
+        size: size_pair(width, height)?,
+        packs,
+    })
+}
+
+fn parse_generate(args: &mut impl Iterator<Item = String>) -> Result<GenerateOptions, String> {
+    let mut options = GenerateOptions { algorithm: Algorithm::Backtracker, size: (0, 0), out: String::new(), seed: 0 };
+    let (mut size, mut out, mut seed) = (None, None, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--algo" => {
+                let name = value(&arg, args.next())?;
+                options.algorithm = Algorithm::parse(&name)
+                    .ok_or_else(|| format!("Unknown algorithm '{}' (available: {})", name, generate::ALGORITHMS))?;
+            }
+            "--size" => size = Some(parse_size(&value(&arg, args.next())?)?),
+            "--out" => out = Some(value(&arg, args.next())?),
+            "--seed" => seed = Some(number(&arg, args.next())?),
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+    options.size = size.ok_or("generate requires --size WxH")?;
+    options.out = out.ok_or("generate requires --out FILE")?;
+    // Sin semilla cada ejecucion da un laberinto distinto
+    options.seed = seed.unwrap_or_else(rand::random);
+    Ok(options)
+}
+
+fn value(flag: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("{} requires a value", flag))
+}
+
+fn count(flag: &str, value: Option<String>) -> Result<usize, String> {
+    let value = value.ok_or_else(|| format!("{} requires a number", flag))?;
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("{}: '{}' is not a positive number", flag, value)),
+    }
+}
+
+fn number(flag: &str, value: Option<String>) -> Result<u64, String> {
+    let value = value.ok_or_else(|| format!("{} requires a number", flag))?;
+    value.parse::<u64>().map_err(|_| format!("{}: '{}' is not a number", flag, value))
+}
+
+fn size_pair(width: Option<usize>, height: Option<usize>) -> Result<Option<(usize, usize)>, String> {
+    match (width, height) {
+        (Some(width), Some(height)) => Ok(Some((width, height))),
+        (None, None) => Ok(None),
+        _ => Err("--width and --height must be used together".to_string()),
+    }
+}
+
+// "12x8" en celdas del laberinto
+fn parse_size(text: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("--size: '{}' is not WxH (e.g. 12x8)", text);
+    let (width, height) = text.split_once('x').ok_or_else(invalid)?;
+    let width: usize = width.parse().map_err(|_| invalid())?;
+    let height: usize = height.parse().map_err(|_| invalid())?;
+    if width < 2 || height < 2 {
+        return Err("--size: a maze needs at least 2x2 cells".to_string());
+    }
+    Ok((width, height))
+}
+
+// "x,y,angulo" con el angulo en grados
+fn parse_pose(text: &str) -> Result<(f64, f64, f64), String> {
+    let values: Vec<f64> = text.split(',')
+        .map(|v| v.trim().parse::<f64>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("--pose: '{}' is not X,Y,ANGLE", text))?;
+    match values[..] {
+        [x, y, angle] => Ok((x, y, angle.to_radians())),
+        _ => Err(format!("--pose: '{}' is not X,Y,ANGLE", text)),
+    }
+}
+
@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use proyecto1::assets::{Assets, BASE_DIR, PACKS_DIR};
+use proyecto1::enemy::Enemy;
+use proyecto1::renderer::Renderer;
+
+use crate::console::{Command, Registry};
+use crate::GameState;
+
+// Archivo de comandos que se ejecuta al arrancar, si existe
+pub const STARTUP_SCRIPT: &str = "autoexec.cfg";
+
+pub fn register_builtin(registry: &mut Registry) {
+    registry.register(Command { name: "help", usage: "help", run: help });
+    registry.register(Command { name: "noclip", usage: "noclip", run: noclip });
+    registry.register(Command { name: "god", usage: "god", run: god });
+    registry.register(Command { name: "tp", usage: "tp X Y", run: teleport });
+    registry.register(Command { name: "spawn", usage: "spawn enemy [X Y]", run: spawn });
+    registry.register(Command { name: "map", usage: "map FILE", run: map });
+    registry.register(Command { name: "reload", usage: "reload", run: reload });
+    registry.register(Command { name: "fov", usage: "fov [DEGREES]", run: fov });
+    registry.register(Command { name: "shake", usage: "shake [AMOUNT]", run: shake });
+    registry.register(Command { name: "timescale", usage: "timescale [FACTOR]", run: timescale });
+    registry.register(Command { name: "screenshot", usage: "screenshot [FILE]", run: screenshot });
+    registry.register(Command { name: "exec", usage: "exec FILE", run: exec });
+}
+
+impl GameState {
+    // Ejecuta una linea de la consola o de un script; la salida va a la consola
+    pub fn run_command(&mut self, line: &str) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&name, args)) = words.split_first() else {
+            return;
+        };
+        let output = match self.commands.find(name) {
+            Some(command) => (command.run)(self, args),
+            None => Err(format!("unknown command '{}' (try 'help')", name)),
+        };
+        match output {
+            Ok(text) => self.console.print(&text),
+            Err(e) => self.console.print(&format!("error: {}", e)),
+        }
+    }
+
+    // Un comando por linea; las lineas vacias y los comentarios (#) se ignoran.
+    // Un script que ya se esta ejecutando (p. ej. `exec` de si mismo) no se
+    // vuelve a abrir.
+    pub fn run_script(&mut self, filename: &str) -> Result<(), String> {
+        let text = fs::read_to_string(filename)
+            .map_err(|e| format!("Failed to read script '{}': {}", filename, e))?;
+        let path = fs::canonicalize(filename).unwrap_or_else(|_| Path::new(filename).to_path_buf());
+        if self.running_scripts.contains(&path) {
+            return Err(format!("script '{}' is already running", filename));
+        }
+        self.running_scripts.push(path);
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            self.run_command(line);
+        }
+        self.running_scripts.pop();
+        Ok(())
+    }
+}
+
+fn parse_number(value: &str) -> Result<f64, String> {
+    value.parse::<f64>().map_err(|_| format!("'{}' is not a number", value))
+}
+
+fn parse_position(state: &GameState, args: &[&str]) -> Result<(f64, f64), String> {
+    let x = parse_number(args[0])?;
+    let y = parse_number(args[1])?;
+    check_walkable(state, x, y)?;
+    Ok((x, y))
+}
+
+fn check_walkable(state: &GameState, x: f64, y: f64) -> Result<(), String> {
+    if x < 0.0 || y < 0.0 || x >= state.map.width() as f64 || y >= state.map.height() as f64 {
+        return Err(format!("{:.2}, {:.2} is outside the map", x, y));
+    }
+    if !state.map.is_walkable(x, y) {
+        return Err(format!("{:.2}, {:.2} is inside a wall", x, y));
+    }
+    Ok(())
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+fn help(state: &mut GameState, _args: &[&str]) -> Result<String, String> {
+    Ok(state.commands.commands().iter().map(|c| c.usage).collect::<Vec<_>>().join("\n"))
+}
+
+fn noclip(state: &mut GameState, _args: &[&str]) -> Result<String, String> {
+    state.player.noclip = !state.player.noclip;
+    Ok(format!("noclip {}", on_off(state.player.noclip)))
+}
+
+fn god(state: &mut GameState, _args: &[&str]) -> Result<String, String> {
+    state.god = !state.god;
+    Ok(format!("god mode {}", on_off(state.god)))
+}
+
+fn teleport(state: &mut GameState, args: &[&str]) -> Result<String, String> {
+    if args.len() != 2 {
+        return Err("usage: tp X Y".to_string());
+    }
+    let (x, y) = parse_position(state, args)?;
+    state.player.x = x;
+    state.player.y = y;
+    Ok(format!("teleported to {:.2}, {:.2}", x, y))
+}
+
+fn spawn(state: &mut GameState, args: &[&str]) -> Result<String, String> {
+    let (x, y) = match args {
+        ["enemy"] => {
+            // Dos celdas delante del jugador
+            let (x, y) = (state.player.x + state.player.angle.cos() * 2.0, state.player.y + state.player.angle.sin() * 2.0);
+            check_walkable(state, x, y)?;
+            (x, y)
+        }
+        ["enemy", x, y] => parse_position(state, &[x, y])?,
+        _ => return Err("usage: spawn enemy [X Y]".to_string()),
+    };
+    state.enemies.push(Enemy::at(x, y, &state.assets));
+    Ok(format!("spawned enemy at {:.2}, {:.2} ({} total)", x, y, state.enemies.len()))
+}
+
+fn map(state: &mut GameState, args: &[&str]) -> Result<String, String> {
+    let [file] = args else {
+        return Err("usage: map FILE".to_string());
+    };
+    state.load_map(file)?;
+    Ok(format!("loaded {}", file))
+}
+
+fn reload(state: &mut GameState, _args: &[&str]) -> Result<String, String> {
+    let assets = Assets::with_packs(BASE_DIR, PACKS_DIR, &state.options.selected)?;
+    state.load_assets(assets)?;
+    Ok("assets reloaded".to_string())
+}
+
+fn fov(state: &mut GameState, args: &[&str]) -> Result<String, String> {
+    if let [degrees] = args {
+        let degrees = parse_number(degrees)?;
+        if !(30.0..=120.0).contains(&degrees) {
+            return Err("fov must be between 30 and 120 degrees".to_string());
+        }
+        state.renderer.fov = degrees.to_radians();
+    }
+    Ok(format!("fov {:.0}", state.renderer.fov.to_degrees()))
+}
+
+fn shake(state: &mut GameState, args: &[&str]) -> Result<String, String> {
+    let amount = match args {
+        [] => 0.5,
+        [amount] => parse_number(amount)?,
+        _ => return Err("usage: shake [AMOUNT]".to_string()),
+    };
+    if !(0.0..=1.0).contains(&amount) {
+        return Err("shake amount must be between 0 and 1".to_string());
+    }
+    state.renderer.camera.shake(amount);
+    Ok(format!("shake {}", amount))
+}
+
+fn timescale(state: &mut GameState, args: &[&str]) -> Result<String, String> {
+    if let [factor] = args {
+        let factor = parse_number(factor)?;
+        if factor <= 0.0 {
+            return Err("timescale must be positive".to_string());
+        }
+        state.time_scale = factor;
+    }
+    Ok(format!("timescale {}", state.time_scale))
+}
+
+// Se guarda al dibujar el proximo cuadro, antes de la consola, para que no salga en la imagen
+fn screenshot(state: &mut GameState, args: &[&str]) -> Result<String, String> {
+    let filename = match args {
+        [file] => file.to_string(),
+        _ => {
+            let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+            format!("screenshot-{}.png", seconds)
+        }
+    };
+    state.screenshot = Some(filename);
+    Ok(String::new())
+}
+
+pub fn save_screenshot(renderer: &Renderer, filename: &str) -> Result<String, String> {
+    let (width, frame) = (renderer.width, &renderer.frame);
+    let image = image::RgbImage::from_fn(width as u32, renderer.height as u32, |x, y| {
+        let color = frame[y as usize * width + x as usize];
+        image::Rgb([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+    });
+    image.save(filename).map_err(|e| format!("Failed to save '{}': {}", filename, e))?;
+    Ok(format!("saved {}", filename))
+}
+
+fn exec(state: &mut GameState, args: &[&str]) -> Result<String, String> {
+    let [file] = args else {
+        return Err("usage: exec FILE".to_string());
+    };
+    state.run_script(file)?;
+    Ok(String::new())
+}
@@ -0,0 +1,162 @@
+use image::{Rgba, RgbaImage};
+
+use crate::config::Config;
+
+// Todo el framebuffer usa 0x00RRGGBB (el formato de minifb); las imagenes
+// se cargan como RGBA y se mezclan con su alfa al dibujarse.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "top_left" => Anchor::TopLeft,
+            "top" => Anchor::Top,
+            "top_right" => Anchor::TopRight,
+            "left" => Anchor::Left,
+            "center" => Anchor::Center,
+            "right" => Anchor::Right,
+            "bottom_left" => Anchor::BottomLeft,
+            "bottom" => Anchor::Bottom,
+            "bottom_right" => Anchor::BottomRight,
+            _ => return None,
+        })
+    }
+
+    // Fraccion (0, 0.5 o 1) del espacio libre en cada eje
+    fn factors(self) -> (f64, f64) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+// Donde se dibuja un sprite en pantalla: anclado a un borde de la pantalla,
+// escalado y desplazado `offset` pixeles desde el ancla.
+#[derive(Clone, Copy, Debug)]
+pub struct Placement {
+    pub anchor: Anchor,
+    pub scale: f64,
+    pub offset: (i32, i32),
+}
+
+impl Placement {
+    pub fn new(anchor: Anchor) -> Self {
+        Placement { anchor, scale: 1.0, offset: (0, 0) }
+    }
+
+    // Lee `anchor`, `scale`, `offset_x` y `offset_y` de una seccion
+    pub fn from_config(config: &Config, section: &str, default: Placement) -> Result<Self, String> {
+        let mut placement = default;
+        if let Some(name) = config.get(section, "anchor") {
+            placement.anchor = Anchor::parse(name)
+                .ok_or_else(|| format!("[{}] unknown anchor '{}'", section, name))?;
+        }
+        if let Some(scale) = config.get_f64(section, "scale")? {
+            if scale <= 0.0 {
+                return Err(format!("[{}] scale must be positive", section));
+            }
+            placement.scale = scale;
+        }
+        if let Some(x) = config.get_f64(section, "offset_x")? {
+            placement.offset.0 = x as i32;
+        }
+        if let Some(y) = config.get_f64(section, "offset_y")? {
+            placement.offset.1 = y as i32;
+        }
+        Ok(placement)
+    }
+
+    // Esquina superior izquierda de una imagen de `size` en una pantalla de `width`x`height`
+    pub fn origin(&self, size: (u32, u32), width: usize, height: usize) -> (i32, i32) {
+        let (fx, fy) = self.anchor.factors();
+        let w = (size.0 as f64 * self.scale).round();
+        let h = (size.1 as f64 * self.scale).round();
+        let x = ((width as f64 - w) * fx).round() as i32 + self.offset.0;
+        let y = ((height as f64 - h) * fy).round() as i32 + self.offset.1;
+        (x, y)
+    }
+}
+
+// Mezcla un pixel RGBA sobre un color 0x00RRGGBB
+pub fn blend_pixel(dst: u32, src: &Rgba<u8>) -> u32 {
+    let [r, g, b, a] = src.0;
+    match a {
+        0 => dst,
+        255 => ((r as u32) << 16) | ((g as u32) << 8) | b as u32,
+        _ => {
+            let a = a as u32;
+            let mix = |s: u8, shift: u32| {
+                let d = (dst >> shift) & 0xFF;
+                ((s as u32 * a + d * (255 - a) + 127) / 255) << shift
+            };
+            mix(r, 16) | mix(g, 8) | mix(b, 0)
+        }
+    }
+}
+
+// Dibuja `image` con su esquina superior izquierda en (x, y), escalada con
+// vecino mas cercano y recortada a los limites del buffer.
+pub fn blit(buffer: &mut [u32], width: usize, height: usize, image: &RgbaImage, x: i32, y: i32, scale: f64) {
+    let (img_w, img_h) = image.dimensions();
+    if img_w == 0 || img_h == 0 || scale <= 0.0 {
+        return;
+    }
+    let out_w = (img_w as f64 * scale).round() as i32;
+    let out_h = (img_h as f64 * scale).round() as i32;
+
+    let x0 = x.max(0);
+    let y0 = y.max(0);
+    let x1 = (x + out_w).min(width as i32);
+    let y1 = (y + out_h).min(height as i32);
+
+    for sy in y0..y1 {
+        let src_y = (((sy - y) as f64 / scale) as u32).min(img_h - 1);
+        let row = sy as usize * width;
+        for sx in x0..x1 {
+            let src_x = (((sx - x) as f64 / scale) as u32).min(img_w - 1);
+            let index = row + sx as usize;
+            buffer[index] = blend_pixel(buffer[index], image.get_pixel(src_x, src_y));
+        }
+    }
+}
+
+pub fn blit_placed(buffer: &mut [u32], width: usize, height: usize, image: &RgbaImage, placement: &Placement) {
+    let (x, y) = placement.origin(image.dimensions(), width, height);
+    blit(buffer, width, height, image, x, y, placement.scale);
+}
+
+// Estira `image` a todo el buffer (vecino mas cercano, sin conservar proporcion)
+pub fn blit_stretched(buffer: &mut [u32], width: usize, height: usize, image: &RgbaImage) {
+    let (img_w, img_h) = image.dimensions();
+    if img_w == 0 || img_h == 0 {
+        return;
+    }
+    for y in 0..height {
+        let src_y = (y as u64 * img_h as u64 / height as u64) as u32;
+        for x in 0..width {
+            let src_x = (x as u64 * img_w as u64 / width as u64) as u32;
+            let index = y * width + x;
+            buffer[index] = blend_pixel(buffer[index], image.get_pixel(src_x, src_y));
+        }
+    }
+}
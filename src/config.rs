@@ -0,0 +1,165 @@
+use std::fs;
+
+use crate::error::GameError;
+
+// Formato sencillo tipo INI/TOML:
+//
+//   # comentario
+//   [seccion]
+//   clave = valor
+//   clave = "texto con espacios"
+//
+// Las claves pueden repetirse dentro de una seccion (ver `get_all`).
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    sections: Vec<Section>,
+}
+
+#[derive(Clone, Debug)]
+struct Section {
+    name: String,
+    entries: Vec<(String, String)>,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Config { sections: Vec::new() }
+    }
+
+    pub fn load(filename: &str) -> Result<Self, GameError> {
+        let text = fs::read_to_string(filename)
+            .map_err(|error| GameError::Io { file: filename.to_string(), error })?;
+        Config::parse(&text).map_err(|e| GameError::config(filename, e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut config = Config::new();
+        let mut current = String::new();
+
+        for (number, raw) in text.lines().enumerate() {
+            let line = strip_comment(raw).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(rest) = line.strip_prefix('[') {
+                let name = rest.strip_suffix(']')
+                    .ok_or_else(|| format!("line {}: unterminated section header", number + 1))?;
+                current = name.trim().to_string();
+                config.section_mut(&current);
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: expected 'key = value'", number + 1))?;
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(format!("line {}: empty key", number + 1));
+            }
+            config.section_mut(&current).entries.push((key.to_string(), unquote(value.trim()).to_string()));
+        }
+
+        Ok(config)
+    }
+
+    pub fn save(&self, filename: &str) -> Result<(), String> {
+        fs::write(filename, self.to_text())
+            .map_err(|e| format!("Failed to write config '{}': {}", filename, e))
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for section in &self.sections {
+            if !section.name.is_empty() {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                out.push_str(&format!("[{}]\n", section.name));
+            }
+            for (key, value) in &section.entries {
+                if value.contains(' ') || value.contains('#') || value.is_empty() {
+                    out.push_str(&format!("{} = \"{}\"\n", key, value));
+                } else {
+                    out.push_str(&format!("{} = {}\n", key, value));
+                }
+            }
+        }
+        out
+    }
+
+    pub fn section_names(&self) -> impl Iterator<Item = &str> {
+        self.sections.iter().map(|s| s.name.as_str())
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.get_all(section, key).pop()
+    }
+
+    pub fn get_all(&self, section: &str, key: &str) -> Vec<&str> {
+        self.sections.iter()
+            .filter(|s| s.name == section)
+            .flat_map(|s| s.entries.iter().filter(|(k, _)| k == key).map(|(_, v)| v.as_str()))
+            .collect()
+    }
+
+    pub fn get_f64(&self, section: &str, key: &str) -> Result<Option<f64>, String> {
+        self.get(section, key)
+            .map(|v| v.parse::<f64>().map_err(|_| format!("[{}] {}: '{}' is not a number", section, key, v)))
+            .transpose()
+    }
+
+    pub fn get_bool(&self, section: &str, key: &str) -> Result<Option<bool>, String> {
+        self.get(section, key)
+            .map(|v| match v {
+                "true" | "yes" | "on" | "1" => Ok(true),
+                "false" | "no" | "off" | "0" => Ok(false),
+                _ => Err(format!("[{}] {}: '{}' is not a boolean", section, key, v)),
+            })
+            .transpose()
+    }
+
+    // Colores como "#rrggbb" o "0xrrggbb", en el formato 0x00RRGGBB del framebuffer
+    pub fn get_color(&self, section: &str, key: &str) -> Result<Option<u32>, String> {
+        self.get(section, key)
+            .map(|v| {
+                let hex = v.strip_prefix('#').or_else(|| v.strip_prefix("0x")).unwrap_or(v);
+                match u32::from_str_radix(hex, 16) {
+                    Ok(color) if hex.len() == 6 => Ok(color),
+                    _ => Err(format!("[{}] {}: '{}' is not a color (use #rrggbb)", section, key, v)),
+                }
+            })
+            .transpose()
+    }
+
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        let entries = &mut self.section_mut(section).entries;
+        match entries.iter_mut().find(|(k, _)| k == key) {
+            Some(entry) => entry.1 = value.to_string(),
+            None => entries.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    fn section_mut(&mut self, name: &str) -> &mut Section {
+        if let Some(index) = self.sections.iter().position(|s| s.name == name) {
+            return &mut self.sections[index];
+        }
+        self.sections.push(Section { name: name.to_string(), entries: Vec::new() });
+        self.sections.last_mut().unwrap()
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn unquote(value: &str) -> &str {
+    value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value)
+}
@@ -0,0 +1,174 @@
+use proyecto1::platform::{Key, KeyRepeat, Platform};
+use proyecto1::ui::{self, UI};
+
+use crate::GameState;
+
+// Lineas que guarda el registro de la consola
+const LOG_LINES: usize = 200;
+// Fraccion de la pantalla que ocupa la consola abierta
+const CONSOLE_HEIGHT: f64 = 0.4;
+
+// Un comando recibe el juego y sus argumentos ya separados por espacios, y
+// devuelve el texto a mostrar (o el error).
+pub type CommandFn = fn(&mut GameState, &[&str]) -> Result<String, String>;
+
+#[derive(Clone, Copy)]
+pub struct Command {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub run: CommandFn,
+}
+
+// Comandos disponibles; cada modulo registra los suyos con `register`
+pub struct Registry {
+    commands: Vec<Command>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry { commands: Vec::new() }
+    }
+
+    // Un comando con el mismo nombre reemplaza al anterior
+    pub fn register(&mut self, command: Command) {
+        self.commands.retain(|c| c.name != command.name);
+        self.commands.push(command);
+        self.commands.sort_by_key(|c| c.name);
+    }
+
+    pub fn find(&self, name: &str) -> Option<Command> {
+        self.commands.iter().find(|c| c.name == name).copied()
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    pub fn complete(&self, prefix: &str) -> Vec<&'static str> {
+        self.commands.iter().map(|c| c.name).filter(|name| name.starts_with(prefix)).collect()
+    }
+}
+
+// Consola desplegable (tecla `): una linea de entrada con historial y
+// completado con Tab, sobre el registro de salida.
+pub struct Console {
+    pub open: bool,
+    input: String,
+    history: Vec<String>,
+    history_pos: Option<usize>,
+    log: Vec<String>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console {
+            open: false,
+            input: String::new(),
+            history: Vec::new(),
+            history_pos: None,
+            log: Vec::new(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    pub fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            self.log.push(line.to_string());
+        }
+        if self.log.len() > LOG_LINES {
+            self.log.drain(..self.log.len() - LOG_LINES);
+        }
+    }
+
+    // Procesa las teclas del cuadro; devuelve la linea si se presiono Enter
+    pub fn handle_input(&mut self, platform: &mut dyn Platform, registry: &Registry) -> Option<String> {
+        let typed = platform.take_text();
+        if !self.open {
+            return None;
+        }
+        self.input.push_str(&typed);
+
+        if platform.is_key_pressed(Key::Backspace, KeyRepeat::Yes) {
+            self.input.pop();
+        }
+        if platform.is_key_pressed(Key::Up, KeyRepeat::Yes) && !self.history.is_empty() {
+            let pos = self.history_pos.map_or(self.history.len() - 1, |pos| pos.saturating_sub(1));
+            self.history_pos = Some(pos);
+            self.input = self.history[pos].clone();
+        }
+        if platform.is_key_pressed(Key::Down, KeyRepeat::Yes) {
+            if let Some(pos) = self.history_pos {
+                if pos + 1 < self.history.len() {
+                    self.history_pos = Some(pos + 1);
+                    self.input = self.history[pos + 1].clone();
+                } else {
+                    self.history_pos = None;
+                    self.input.clear();
+                }
+            }
+        }
+        if platform.is_key_pressed(Key::Tab, KeyRepeat::No) {
+            self.complete(registry);
+        }
+
+        if platform.is_key_pressed(Key::Enter, KeyRepeat::No) {
+            let line = std::mem::take(&mut self.input).trim().to_string();
+            self.history_pos = None;
+            if line.is_empty() {
+                return None;
+            }
+            if self.history.last() != Some(&line) {
+                self.history.push(line.clone());
+            }
+            self.print(&format!("] {}", line));
+            return Some(line);
+        }
+        None
+    }
+
+    // Completa el nombre del comando; si hay varias opciones las muestra y
+    // completa hasta el prefijo comun
+    fn complete(&mut self, registry: &Registry) {
+        if self.input.contains(' ') {
+            return;
+        }
+        let matches = registry.complete(&self.input);
+        match matches.as_slice() {
+            [] => {}
+            [name] => self.input = format!("{} ", name),
+            names => {
+                let common = names.iter().skip(1).fold(names[0].to_string(), |common, name| {
+                    common.chars().zip(name.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a).collect()
+                });
+                self.input = common;
+                self.print(&names.join("  "));
+            }
+        }
+    }
+
+    pub fn render(&self, ui: &UI, buffer: &mut [u32], width: usize, height: usize) {
+        let console_height = (height as f64 * CONSOLE_HEIGHT) as usize;
+        for pixel in &mut buffer[..console_height * width] {
+            *pixel = ui::blend(*pixel, 0x101018, 0.85);
+        }
+        if console_height < 12 {
+            return;
+        }
+
+        // Las lineas largas se recortan; de la entrada se ve el final
+        let columns = (width.saturating_sub(12) / 8).max(4);
+        let prompt = format!("] {}_", self.input);
+        let skip = prompt.chars().count().saturating_sub(columns);
+        let prompt_y = console_height - 12;
+        ui.draw_text(buffer, width, &prompt.chars().skip(skip).collect::<String>(), 6, prompt_y, 0xFFFFFF);
+
+        let visible = prompt_y / 10;
+        for (i, line) in self.log.iter().rev().take(visible).enumerate() {
+            let line: String = line.chars().take(columns).collect();
+            ui.draw_text(buffer, width, &line, 6, prompt_y - (i + 1) * 10, 0xB0B0B0);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use proyecto1::enemy::Enemy;
+use proyecto1::map::Map;
+use proyecto1::player::Player;
+use proyecto1::renderer::{FrameTimes, Renderer, RAY_STEP};
+use proyecto1::ui::UI;
+
+use crate::console::{Command, Registry};
+use crate::GameState;
+
+// Cuadros que guarda la grafica
+const HISTORY: usize = 120;
+// Pixeles por milisegundo en la grafica
+const GRAPH_SCALE: f64 = 1.0;
+// Cada cuantas columnas se dibuja un rayo en el minimapa
+const RAY_STRIDE: usize = 12;
+
+const STAGE_COLORS: [u32; 4] = [0xE04040, 0x40C040, 0x4080FF, 0xE0C000];
+
+// Informacion de depuracion sobre el juego (tecla F3): grafica de los ultimos
+// cuadros por etapa, posicion del jugador, estado del enemigo y rayos.
+pub struct DebugOverlay {
+    pub visible: bool,
+    history: VecDeque<(f64, FrameTimes)>,
+}
+
+// Comandos de consola de este modulo
+pub fn register_commands(registry: &mut Registry) {
+    registry.register(Command { name: "debug", usage: "debug", run: toggle });
+}
+
+fn toggle(state: &mut GameState, _args: &[&str]) -> Result<String, String> {
+    state.debug.visible = !state.debug.visible;
+    Ok(format!("debug overlay {}", if state.debug.visible { "on" } else { "off" }))
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        DebugOverlay { visible: false, history: VecDeque::with_capacity(HISTORY) }
+    }
+
+    // Se llama una vez por cuadro, este o no visible, para que la grafica tenga historia
+    pub fn record(&mut self, dt: f64, times: FrameTimes) {
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back((dt, times));
+    }
+
+    pub fn render(&self, ui: &UI, renderer: &mut Renderer, map: &Map, player: &Player, enemies: &[Enemy]) {
+        self.render_minimap_rays(renderer, map, player, enemies);
+
+        let (width, height) = (renderer.width, renderer.height);
+        let times = renderer.times;
+        let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+        let frame_ms = self.history.back().map_or(0.0, |(dt, _)| dt * 1000.0);
+        let steps: f64 = renderer.depth.iter().map(|d| (d / RAY_STEP).ceil()).sum();
+        let (cell_x, cell_y) = (player.x as usize, player.y as usize);
+
+        let lines = [
+            format!("FRAME {:6.2} MS", frame_ms),
+            format!("RAYS    {:6.2} MS", ms(times.raycast)),
+            format!("FLOOR   {:6.2} MS", ms(times.floor)),
+            format!("SPRITES {:6.2} MS", ms(times.sprites)),
+            format!("UI      {:6.2} MS", ms(times.ui)),
+            format!("RAY COUNT {} STEPS {}", width, steps as u64),
+            format!("POS {:.2}, {:.2} ANGLE {:.0}", player.x, player.y, player.angle.to_degrees().rem_euclid(360.0)),
+            format!("CELL {}, {} '{}'", cell_x, cell_y, map.get_cell(cell_x, cell_y)),
+            format!("MOVE {:?} SPEED {:.2} NOISE {:.1}", player.movement.mode, player.movement.speed(), player.noise()),
+            format!("ENEMIES {}", enemies.len()),
+        ];
+        // El enemigo mas cercano
+        let nearest = enemies.iter().min_by(|a, b| a.distance_to(player).total_cmp(&b.distance_to(player)));
+        let nearest = nearest.map(|enemy| [
+            format!("ENEMY {:?} {:?} PATH {}", enemy.state, enemy.anim, enemy.path.len()),
+            format!("ENEMY DIST {:.2}", enemy.distance_to(player)),
+        ]);
+        let lines = lines.iter().chain(nearest.iter().flatten());
+        let top = renderer.minimap_size() + 10;
+        for (i, line) in lines.enumerate() {
+            let color = if (1..5).contains(&i) { STAGE_COLORS[i - 1] } else { 0xFFFFFF };
+            ui.draw_text(&mut renderer.frame, width, line, 10, top + i * 10, color);
+        }
+
+        self.render_graph(&mut renderer.frame, width, height);
+    }
+
+    // Barras apiladas por etapa, abajo a la izquierda; la linea marca 15 FPS
+    fn render_graph(&self, buffer: &mut [u32], width: usize, height: usize) {
+        let graph_height = (height / 4).max(1);
+        let bottom = height.saturating_sub(10);
+        let left = 10;
+        if bottom < graph_height || left + HISTORY > width {
+            return;
+        }
+
+        for (i, (_, times)) in self.history.iter().enumerate() {
+            let x = left + i;
+            let stages = [times.raycast, times.floor, times.sprites, times.ui];
+            let mut y = bottom;
+            for (stage, color) in stages.iter().zip(STAGE_COLORS) {
+                let bar = ((stage.as_secs_f64() * 1000.0 * GRAPH_SCALE) as usize).min(y - (bottom - graph_height));
+                for row in y - bar..y {
+                    buffer[row * width + x] = color;
+                }
+                y -= bar;
+            }
+        }
+
+        let target = (1000.0 / 15.0 * GRAPH_SCALE) as usize;
+        if target < graph_height {
+            let row = bottom - target;
+            for x in left..left + HISTORY {
+                buffer[row * width + x] = 0xFFFFFF;
+            }
+        }
+    }
+
+    // Rayos del jugador y camino del enemigo sobre el minimapa
+    fn render_minimap_rays(&self, renderer: &mut Renderer, map: &Map, player: &Player, enemies: &[Enemy]) {
+        let scale = renderer.minimap_scale(map);
+        let size = renderer.minimap_size();
+        let width = renderer.width;
+
+        for x in (0..width).step_by(RAY_STRIDE) {
+            let angle = renderer.ray_angle(player, x);
+            let end = (player.x + angle.cos() * renderer.depth[x], player.y + angle.sin() * renderer.depth[x]);
+            draw_line(&mut renderer.frame, width, size, (player.x * scale, player.y * scale), (end.0 * scale, end.1 * scale), 0xFFD040);
+        }
+
+        for enemy in enemies {
+            let mut from = (enemy.x, enemy.y);
+            for &(cx, cy) in &enemy.path {
+                let to = (cx as f64 + 0.5, cy as f64 + 0.5);
+                draw_line(&mut renderer.frame, width, size, (from.0 * scale, from.1 * scale), (to.0 * scale, to.1 * scale), 0x00C000);
+                from = to;
+            }
+
+            // Hacia donde mira el enemigo
+            let facing = (enemy.x + enemy.facing.cos() * 0.6, enemy.y + enemy.facing.sin() * 0.6);
+            draw_line(&mut renderer.frame, width, size, (enemy.x * scale, enemy.y * scale), (facing.0 * scale, facing.1 * scale), 0xFF00FF);
+        }
+    }
+}
+
+// Linea recortada al cuadrado del minimapa (`size` x `size`)
+fn draw_line(buffer: &mut [u32], width: usize, size: usize, from: (f64, f64), to: (f64, f64), color: u32) {
+    let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).ceil() as usize;
+    for i in 0..=steps {
+        let t = if steps == 0 { 0.0 } else { i as f64 / steps as f64 };
+        let x = from.0 + (to.0 - from.0) * t;
+        let y = from.1 + (to.1 - from.1) * t;
+        if x < 0.0 || y < 0.0 || x as usize >= size || y as usize >= size {
+            continue;
+        }
+        buffer[y as usize * width + x as usize] = color;
+    }
+}
@@ -0,0 +1,167 @@
+use proyecto1::config::Config;
+use proyecto1::platform::Platform;
+
+// Como se lleva la imagen de la resolucion de render al tamano de la ventana.
+// Se configura en [video] de settings.cfg:
+//
+//   [video]
+//   render_width = 420        # resolucion fija de render...
+//   render_height = 290
+//   render_scale = 0.5        # ...o una fraccion del tamano de la ventana
+//   upscale = integer         # integer | nearest
+//   aspect = fit              # fit (barras negras) | fill (recorta) | stretch
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Upscale {
+    Integer,
+    Nearest,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Aspect {
+    Fit,
+    Fill,
+    Stretch,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Resolution {
+    Fixed(usize, usize),
+    Scale(f64),
+}
+
+pub struct Display {
+    pub resolution: Resolution,
+    pub upscale: Upscale,
+    pub aspect: Aspect,
+    output: Vec<u32>,
+    // Columna/fila de la imagen que va en cada columna/fila de la ventana
+    source_x: Vec<Option<usize>>,
+    source_y: Vec<Option<usize>>,
+    // (ventana, imagen) con los que se calcularon las tablas
+    layout: ((usize, usize), (usize, usize)),
+}
+
+impl Display {
+    pub fn new(resolution: Resolution) -> Self {
+        Display {
+            resolution,
+            upscale: Upscale::Nearest,
+            aspect: Aspect::Fit,
+            output: Vec::new(),
+            source_x: Vec::new(),
+            source_y: Vec::new(),
+            layout: ((0, 0), (0, 0)),
+        }
+    }
+
+    pub fn from_config(config: &Config, default_size: (usize, usize)) -> Result<Self, String> {
+        let width = config.get_f64("video", "render_width")?.map_or(default_size.0, |w| w as usize);
+        let height = config.get_f64("video", "render_height")?.map_or(default_size.1, |h| h as usize);
+        if width == 0 || height == 0 {
+            return Err("[video] render size must be positive".to_string());
+        }
+        let resolution = match config.get_f64("video", "render_scale")? {
+            Some(scale) if scale > 0.0 && scale <= 1.0 => Resolution::Scale(scale),
+            Some(scale) => return Err(format!("[video] render_scale {} must be in (0, 1]", scale)),
+            None => Resolution::Fixed(width, height),
+        };
+
+        let mut display = Display::new(resolution);
+        display.upscale = match config.get("video", "upscale").unwrap_or("nearest") {
+            "integer" => Upscale::Integer,
+            "nearest" => Upscale::Nearest,
+            other => return Err(format!("[video] unknown upscale '{}'", other)),
+        };
+        display.aspect = match config.get("video", "aspect").unwrap_or("fit") {
+            "fit" => Aspect::Fit,
+            "fill" => Aspect::Fill,
+            "stretch" => Aspect::Stretch,
+            other => return Err(format!("[video] unknown aspect '{}'", other)),
+        };
+        Ok(display)
+    }
+
+    // Resolucion de render para una ventana de `window_size`
+    pub fn render_size(&self, window_size: (usize, usize)) -> (usize, usize) {
+        match self.resolution {
+            Resolution::Fixed(width, height) => (width, height),
+            Resolution::Scale(scale) => (
+                ((window_size.0 as f64 * scale) as usize).max(1),
+                ((window_size.1 as f64 * scale) as usize).max(1),
+            ),
+        }
+    }
+
+    // Escala `frame` al tamano actual de la ventana y lo muestra
+    pub fn present(&mut self, platform: &mut dyn Platform, frame: &[u32], width: usize, height: usize) {
+        let window_size = platform.size();
+        if window_size.0 == 0 || window_size.1 == 0 {
+            platform.update();
+            return;
+        }
+        if self.layout != (window_size, (width, height)) {
+            self.relayout(window_size, (width, height));
+        }
+
+        let (window_width, _) = window_size;
+        for (wy, source_y) in self.source_y.iter().enumerate() {
+            let row = &mut self.output[wy * window_width..(wy + 1) * window_width];
+            match source_y {
+                Some(sy) => {
+                    let source_row = &frame[sy * width..(sy + 1) * width];
+                    for (pixel, source_x) in row.iter_mut().zip(&self.source_x) {
+                        *pixel = source_x.map_or(0, |sx| source_row[sx]);
+                    }
+                }
+                None => row.fill(0),
+            }
+        }
+
+        platform.present(&self.output, window_size.0, window_size.1);
+    }
+
+    // Pixel de la imagen bajo un punto de la ventana (p. ej. el mouse), segun
+    // el ultimo `present`; None si cae en las barras negras
+    pub fn to_frame(&self, window_pos: (f32, f32)) -> Option<(usize, usize)> {
+        let (x, y) = window_pos;
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let source_x = (*self.source_x.get(x as usize)?)?;
+        let source_y = (*self.source_y.get(y as usize)?)?;
+        Some((source_x, source_y))
+    }
+
+    fn relayout(&mut self, window_size: (usize, usize), frame_size: (usize, usize)) {
+        let (ww, wh) = (window_size.0 as f64, window_size.1 as f64);
+        let (fw, fh) = (frame_size.0 as f64, frame_size.1 as f64);
+
+        let (mut sx, mut sy) = match self.aspect {
+            Aspect::Fit => ((ww / fw).min(wh / fh), (ww / fw).min(wh / fh)),
+            Aspect::Fill => ((ww / fw).max(wh / fh), (ww / fw).max(wh / fh)),
+            Aspect::Stretch => (ww / fw, wh / fh),
+        };
+
+        if self.upscale == Upscale::Integer {
+            // Fill redondea hacia arriba para seguir cubriendo la ventana
+            let round = |s: f64| if self.aspect == Aspect::Fill { s.ceil() } else { s.floor().max(1.0) };
+            sx = round(sx);
+            sy = round(sy);
+        }
+
+        let offset_x = (ww - fw * sx) / 2.0;
+        let offset_y = (wh - fh * sy) / 2.0;
+        let map = |window: usize, offset: f64, scale: f64, size: usize| {
+            (0..window)
+                .map(|w| {
+                    let source = (w as f64 + 0.5 - offset) / scale;
+                    (source >= 0.0 && (source as usize) < size).then_some(source as usize)
+                })
+                .collect::<Vec<_>>()
+        };
+        self.source_x = map(window_size.0, offset_x, sx, frame_size.0);
+        self.source_y = map(window_size.1, offset_y, sy, frame_size.1);
+        self.output = vec![0; window_size.0 * window_size.1];
+        self.layout = (window_size, frame_size);
+    }
+}
@@ -0,0 +1,334 @@
+use std::fs;
+use std::time::Instant;
+
+use proyecto1::enemy::Enemy;
+use proyecto1::map::Map;
+use proyecto1::player::Player;
+use proyecto1::platform::{Key, KeyRepeat, MouseButton, Platform};
+use proyecto1::tension::Tension;
+use proyecto1::ui::{self, UI};
+
+use crate::display::Display;
+use crate::{load_level, GameState, State};
+
+// Cuantos cambios se pueden deshacer
+const UNDO_LIMIT: usize = 100;
+// Alto de la barra de estado bajo la cuadricula
+const STATUS_HEIGHT: usize = 24;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Tool {
+    Wall,
+    Floor,
+    Player,
+    Enemy,
+    Goal,
+    Checkpoint,
+}
+
+const TOOLS: [Tool; 6] = [Tool::Wall, Tool::Floor, Tool::Player, Tool::Enemy, Tool::Goal, Tool::Checkpoint];
+const TOOL_KEYS: [Key; 6] = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6];
+
+impl Tool {
+    fn cell(self) -> char {
+        match self {
+            Tool::Wall => '+',
+            Tool::Floor => ' ',
+            Tool::Player => 'p',
+            Tool::Enemy => 'E',
+            Tool::Goal => 'g',
+            Tool::Checkpoint => 'c',
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Tool::Wall => "Wall",
+            Tool::Floor => "Floor",
+            Tool::Player => "Player",
+            Tool::Enemy => "Enemy",
+            Tool::Goal => "Goal",
+            Tool::Checkpoint => "Checkpoint",
+        }
+    }
+}
+
+fn cell_color(cell: char) -> u32 {
+    match cell {
+        '+' | '-' | '|' => 0x8A8A8A,
+        'p' => 0x30C030,
+        'E' => 0xD03030,
+        'g' => 0xE0C020,
+        'c' => 0x40E0A0,
+        _ => 0x202020,
+    }
+}
+
+// Lo que el juego tiene que hacer despues de un cuadro del editor
+pub enum EditorAction {
+    None,
+    Save,
+    // Vista en primera persona desde el centro de esta celda
+    Preview(usize, usize),
+    Exit,
+}
+
+// Editor del mapa visto desde arriba. Trabaja sobre una copia: el mapa del
+// juego solo cambia al guardar o al previsualizar.
+pub struct Editor {
+    pub map: Map,
+    tool: Tool,
+    cursor: Option<(usize, usize)>,
+    undo: Vec<Map>,
+    redo: Vec<Map>,
+    // Un trazo con el mouse apretado se deshace de una vez
+    painting: bool,
+    pub dirty: bool,
+    // Se pidio salir con cambios sin guardar; un segundo Backspace sale
+    confirm_exit: bool,
+    pub message: Option<String>,
+}
+
+impl Editor {
+    pub fn new(map: &Map) -> Self {
+        Editor {
+            map: map.clone(),
+            tool: Tool::Wall,
+            cursor: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            painting: false,
+            dirty: false,
+            confirm_exit: false,
+            message: None,
+        }
+    }
+
+    pub fn handle_input(&mut self, platform: &dyn Platform, display: &Display, width: usize, height: usize) -> EditorAction {
+        for (tool, key) in TOOLS.iter().zip(TOOL_KEYS) {
+            if platform.is_key_pressed(key, KeyRepeat::No) {
+                self.tool = *tool;
+            }
+        }
+
+        self.cursor = platform.mouse_pos()
+            .and_then(|pos| display.to_frame(pos))
+            .and_then(|(x, y)| self.cell_at(x, y, width, height));
+        let left = platform.is_mouse_down(MouseButton::Left);
+        let right = platform.is_mouse_down(MouseButton::Right);
+        match self.cursor {
+            Some((x, y)) if left => self.paint(x, y, self.tool),
+            Some((x, y)) if right => self.paint(x, y, Tool::Floor),
+            _ => {}
+        }
+        if !left && !right {
+            self.painting = false;
+        }
+
+        let ctrl = platform.is_key_down(Key::LeftCtrl) || platform.is_key_down(Key::RightCtrl);
+        let shift = platform.is_key_down(Key::LeftShift) || platform.is_key_down(Key::RightShift);
+        if ctrl && platform.is_key_pressed(Key::Z, KeyRepeat::Yes) {
+            if shift { self.redo() } else { self.undo() }
+        } else if ctrl && platform.is_key_pressed(Key::Y, KeyRepeat::Yes) {
+            self.redo();
+        } else if ctrl && platform.is_key_pressed(Key::S, KeyRepeat::No) {
+            return EditorAction::Save;
+        } else if platform.is_key_pressed(Key::P, KeyRepeat::No) {
+            match self.cursor {
+                Some((x, y)) if self.map.is_walkable(x as f64 + 0.5, y as f64 + 0.5) => return EditorAction::Preview(x, y),
+                Some(_) => self.message = Some("Can't preview from inside a wall".to_string()),
+                None => self.message = Some("Point at a cell to preview from it".to_string()),
+            }
+        } else if platform.is_key_pressed(Key::Backspace, KeyRepeat::No) {
+            if !self.dirty || self.confirm_exit {
+                return EditorAction::Exit;
+            }
+            self.confirm_exit = true;
+            self.message = Some("Unsaved changes: Backspace again to discard them".to_string());
+        }
+        EditorAction::None
+    }
+
+    fn paint(&mut self, x: usize, y: usize, tool: Tool) {
+        let cell = tool.cell();
+        if self.map.get_cell(x, y) == cell {
+            return;
+        }
+        if !self.painting {
+            self.push_undo();
+            self.painting = true;
+        }
+        // Solo hay un inicio del jugador: el anterior pasa a ser piso
+        if tool == Tool::Player {
+            for (px, py) in self.map.find('p') {
+                self.map.set_cell(px, py, ' ');
+            }
+        }
+        self.map.set_cell(x, y, cell);
+        self.mark_dirty();
+        self.message = None;
+    }
+
+    // Un cambio nuevo cancela la confirmacion de salida pendiente
+    fn mark_dirty(&mut self) {
+        self.dirty = true;
+        self.confirm_exit = false;
+    }
+
+    fn push_undo(&mut self) {
+        if self.undo.len() == UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(self.map.clone());
+        self.redo.clear();
+    }
+
+    fn undo(&mut self) {
+        if let Some(map) = self.undo.pop() {
+            self.redo.push(std::mem::replace(&mut self.map, map));
+            self.mark_dirty();
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(map) = self.redo.pop() {
+            self.undo.push(std::mem::replace(&mut self.map, map));
+            self.mark_dirty();
+        }
+    }
+
+    // Tamano de cada celda (0 si no cabe) y esquina superior izquierda de la cuadricula
+    fn layout(&self, width: usize, height: usize) -> (usize, usize, usize) {
+        let (columns, rows) = (self.map.width().max(1), self.map.height().max(1));
+        let cell = (width / columns).min(height.saturating_sub(STATUS_HEIGHT) / rows);
+        let left = width.saturating_sub(cell * columns) / 2;
+        let top = height.saturating_sub(STATUS_HEIGHT).saturating_sub(cell * rows) / 2;
+        (cell, left, top)
+    }
+
+    fn cell_at(&self, x: usize, y: usize, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (cell, left, top) = self.layout(width, height);
+        if cell == 0 {
+            return None;
+        }
+        let (cx, cy) = (x.checked_sub(left)? / cell, y.checked_sub(top)? / cell);
+        (cx < self.map.width() && cy < self.map.height()).then_some((cx, cy))
+    }
+
+    pub fn render(&self, ui: &UI, buffer: &mut [u32], width: usize, height: usize) {
+        buffer.fill(0);
+        let (cell, left, top) = self.layout(width, height);
+        // Con cell == 0 (ventana diminuta) solo se dibuja la barra de estado
+        for cy in 0..self.map.height() {
+            for cx in 0..self.map.width() {
+                let color = cell_color(self.map.get_cell(cx, cy));
+                let hovered = self.cursor == Some((cx, cy));
+                for py in 0..cell {
+                    let row = (top + cy * cell + py) * width;
+                    for px in 0..cell {
+                        let edge = px == 0 || py == 0 || px == cell - 1 || py == cell - 1;
+                        let index = row + left + cx * cell + px;
+                        buffer[index] = match (edge, hovered) {
+                            (true, true) => 0xFFFFFF,
+                            (true, false) => ui::blend(color, 0x000000, 0.4),
+                            _ => color,
+                        };
+                    }
+                }
+            }
+        }
+
+        let status_y = height.saturating_sub(STATUS_HEIGHT) + 2;
+        let mut x = 4;
+        for (i, tool) in TOOLS.iter().enumerate() {
+            let label = format!("{} {}", i + 1, tool.name());
+            let color = if *tool == self.tool { 0xFFFFFF } else { 0x707070 };
+            ui.draw_text(buffer, width, &label, x, status_y, color);
+            x += (label.len() + 1) * 8;
+        }
+        let help = match &self.message {
+            Some(message) => message.clone(),
+            None => {
+                let cursor = self.cursor.map_or(String::new(), |(cx, cy)| format!("{},{} ", cx, cy));
+                let saved = if self.dirty { "*" } else { "" };
+                format!("{}{}LMB paint RMB erase ^Z/^Y undo ^S save P preview", cursor, saved)
+            }
+        };
+        let help: String = help.chars().take(width / 8).collect();
+        ui.draw_text(buffer, width, &help, 4, status_y + 11, 0xFFFFFF);
+    }
+}
+
+impl GameState {
+    pub fn enter_editor(&mut self) {
+        self.editor = Some(Editor::new(&self.map));
+        self.current_state = State::Editor;
+    }
+
+    pub fn show_editor(&mut self, platform: &mut dyn Platform) {
+        let Some(mut editor) = self.editor.take() else {
+            self.current_state = State::Welcome;
+            return;
+        };
+        let (width, height) = (self.renderer.width, self.renderer.height);
+        let action = editor.handle_input(platform, &self.display, width, height);
+        self.present_screen(platform, |ui, buffer, width, height| editor.render(ui, buffer, width, height));
+
+        match action {
+            EditorAction::None => {}
+            EditorAction::Save => {
+                editor.message = Some(self.save_map(&mut editor).unwrap_or_else(|e| format!("Not saved: {}", e)));
+            }
+            EditorAction::Preview(x, y) => self.preview(&editor.map, x, y),
+            EditorAction::Exit => {
+                // Se vuelve al mapa guardado; lo que no se guardo se pierde
+                if let Err(e) = self.load_map(&self.map_file.clone()) {
+                    println!("{}", e);
+                }
+                self.current_state = State::Welcome;
+                return;
+            }
+        }
+        self.editor = Some(editor);
+    }
+
+    // Solo se guarda un mapa que pasa `Map::check`
+    fn save_map(&mut self, editor: &mut Editor) -> Result<String, String> {
+        let problems = editor.map.check();
+        if !problems.is_empty() {
+            return Err(problems.join("; "));
+        }
+        let path = self.assets.path(&self.map_file)
+            .ok_or_else(|| format!("'{}' comes from a .zip pack", self.map_file))?;
+        fs::write(&path, editor.map.to_text())
+            .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+        editor.dirty = false;
+        Ok(format!("Saved {}", path.display()))
+    }
+
+    // Juega el mapa del editor desde el centro de la celda (x, y)
+    fn preview(&mut self, map: &Map, x: usize, y: usize) {
+        self.map = map.clone();
+        let (atmosphere, lighting) = load_level(&self.assets, &self.map_file, &self.map);
+        self.renderer.atmosphere = atmosphere;
+        self.renderer.lighting = lighting;
+        // Salud, vidas y punto de control empiezan de cero en cada prueba
+        self.player = Player::at(x as f64 + 0.5, y as f64 + 0.5);
+        self.enemies = Enemy::spawn_all(&self.map, &self.assets);
+        self.tension = Tension::new();
+        self.last_frame_time = Instant::now();
+        self.current_state = State::Playing;
+    }
+
+    pub fn is_previewing(&self) -> bool {
+        self.editor.is_some()
+    }
+
+    pub fn return_to_editor(&mut self, message: Option<&str>) {
+        self.audio.stop_sfx();
+        if let Some(editor) = &mut self.editor {
+            editor.message = message.map(str::to_string);
+        }
+        self.current_state = State::Editor;
+    }
+}
@@ -1,85 +1,318 @@
-use image::{DynamicImage, GenericImageView, RgbaImage, Rgba};
+use std::f64::consts::PI;
+use image::RgbaImage;
 use crate::map::Map;
 use crate::player::Player;
-use glam::Vec2;
+use crate::assets::Assets;
+use crate::pathfinding;
+use crate::animated_sprite::{AnimatedSprite, FrameEvent};
+
+// Distancia maxima (en celdas) a la que el enemigo ve al jugador
+const SIGHT_RANGE: f64 = 8.0;
+const CHASE_SPEED: f64 = 1.2;
+// Segundos que sigue buscando despues de perder de vista al jugador
+const GIVE_UP_TIME: f64 = 4.0;
+const REPATH_INTERVAL: f64 = 0.5;
+// Distancia a la que pasa a la animacion de ataque
+const ATTACK_RANGE: f64 = 1.0;
+// Distancia a la que golpea, dano de cada golpe y segundos entre golpes
+const CONTACT_RANGE: f64 = 0.5;
+const ATTACK_DAMAGE: f64 = 35.0;
+const ATTACK_COOLDOWN: f64 = 1.0;
+
+pub const ATLAS: &str = "enemy.atlas";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EnemyState {
+    Idle,
+    Chasing,
+}
+
+// Que clip del atlas del enemigo se esta reproduciendo
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EnemyAnim {
+    Idle,
+    Walk,
+    Attack,
+}
+
+impl EnemyAnim {
+    fn clip_name(self) -> &'static str {
+        match self {
+            EnemyAnim::Idle => "idle",
+            EnemyAnim::Walk => "walk",
+            EnemyAnim::Attack => "attack",
+        }
+    }
+}
 
 pub struct Enemy {
     pub x: f64,
     pub y: f64,
-    pub texture: DynamicImage,
+    // Hacia donde mira, en radianes (mismo sistema que `Player::angle`)
+    pub facing: f64,
+    pub anim: EnemyAnim,
+    sprite: Option<AnimatedSprite>,
+    pub state: EnemyState,
     pub speed: f64,
+    pub path: Vec<(usize, usize)>,
+    target: (usize, usize),
+    time_since_seen: f64,
+    repath_timer: f64,
+    attack_timer: f64,
 }
 
 impl Enemy {
-    pub fn new(map: &Map) -> Self {
-        let (x, y) = map.find_enemy_start();
-        let texture = match image::open("assets/enemy.png") {
-            Ok(img) => img,
+    // Un enemigo en cada celda 'E' del mapa
+    pub fn spawn_all(map: &Map, assets: &Assets) -> Vec<Enemy> {
+        map.enemy_starts().into_iter().map(|(x, y)| Enemy::at(x, y, assets)).collect()
+    }
+
+    pub fn at(x: f64, y: f64, assets: &Assets) -> Self {
+        let sprite = match AnimatedSprite::from_atlas(assets, ATLAS) {
+            Ok(sprite) => Some(sprite),
             Err(e) => {
-                println!("Error loading enemy texture: {:?}", e);
-                DynamicImage::new_rgba8(0, 0)
+                println!("Error loading enemy sprite: {}", e);
+                None
             }
         };
-        let speed = 0.0;
-        
-        Enemy { x, y, texture, speed }
+        let mut enemy = Enemy {
+            x,
+            y,
+            facing: 0.0,
+            anim: EnemyAnim::Walk,
+            sprite,
+            state: EnemyState::Idle,
+            speed: CHASE_SPEED,
+            path: Vec::new(),
+            target: (x as usize, y as usize),
+            time_since_seen: 0.0,
+            repath_timer: 0.0,
+            attack_timer: 0.0,
+        };
+        enemy.set_anim(EnemyAnim::Idle);
+        enemy
     }
-    
 
-    pub fn update(&mut self, _map: &Map, _player: &Player, _dt: f64) {
-        
+    // Cambia el atlas (p. ej. al recargarlo) manteniendo la animacion actual
+    pub fn set_sprite(&mut self, mut sprite: AnimatedSprite) {
+        if sprite.has_clip(self.anim.clip_name()) {
+            sprite.play(self.anim.clip_name());
+        }
+        self.sprite = Some(sprite);
     }
 
-    pub fn has_caught_player(&self, player: &Player) -> bool {
-        let distance = ((self.x - player.x).powi(2) + (self.y - player.y).powi(2)).sqrt();
-        distance < 0.3 
+    pub fn sprite_files(&self) -> &[String] {
+        self.sprite.as_ref().map_or(&[], |sprite| sprite.files())
     }
-    
-    pub fn render(&self, buffer: &mut Vec<u32>, width: usize, height: usize, player: &Player, z_buffer: &[f64]) {
-        let dx = self.x - player.x;
-        let dy = self.y - player.y;
-        
-        let distance = (dx * dx + dy * dy).sqrt();
-        let angle = dy.atan2(dx) - player.angle;
-        
-        // Adjust angle to be within -PI to PI
-        let angle = (angle + std::f64::consts::PI * 3.0) % (std::f64::consts::PI * 2.0) - std::f64::consts::PI;
-    
-        // Check if enemy is in front of the player and within field of view
-        if distance > 0.5 && angle.abs() < std::f64::consts::FRAC_PI_4 {
-            let sprite_size = (height as f64 / distance) as usize;
-            let h_offset = ((angle / std::f64::consts::FRAC_PI_4 + 1.0) * width as f64 / 2.0) as i32;
-            
-            let (tex_width, tex_height) = self.texture.dimensions();
-            let texture = self.texture.to_rgba8();
-    
-            for sx in 0..sprite_size {
-                let screen_x = h_offset + sx as i32 - (sprite_size / 2) as i32;
-                if screen_x < 0 || screen_x >= width as i32 {
-                    continue;
-                }
-                
-                let tex_x = (sx as f64 / sprite_size as f64 * tex_width as f64) as u32;
-    
-                if z_buffer[screen_x as usize] > distance {
-                    for sy in 0..sprite_size {
-                        let y = height / 2 + sy - sprite_size / 2;
-                        if y >= height {
-                            break;
-                        }
-    
-                        let tex_y = (sy as f64 / sprite_size as f64 * tex_height as f64) as u32;
-                        let color = texture.get_pixel(tex_x, tex_y);
-    
-                        if color[3] > 0 {
-                            let idx = y * width + screen_x as usize;
-                            if idx < buffer.len() {
-                                buffer[idx] = u32::from_be_bytes([color[0], color[1], color[2], color[3]]);
-                            }
-                        }
-                    }
+
+    fn set_anim(&mut self, anim: EnemyAnim) {
+        if self.anim == anim {
+            return;
+        }
+        self.anim = anim;
+        if let Some(sprite) = &mut self.sprite {
+            if sprite.has_clip(anim.clip_name()) {
+                sprite.play(anim.clip_name());
+            }
+        }
+    }
+
+    // Mueve al enemigo y avanza su animacion; devuelve los eventos de cuadro
+    // del clip (p. ej. pasos) para que el juego los haga sonar.
+    pub fn update(&mut self, map: &Map, player: &Player, dt: f64) -> Vec<FrameEvent> {
+        let (old_x, old_y) = (self.x, self.y);
+        self.attack_timer = (self.attack_timer - dt).max(0.0);
+        let sees_player = self.watch(map, player, dt);
+        if self.state == EnemyState::Chasing {
+            self.pursue(map, player, sees_player, dt);
+        }
+
+        let (dx, dy) = (self.x - old_x, self.y - old_y);
+        let moved = dx != 0.0 || dy != 0.0;
+        if moved {
+            self.facing = dy.atan2(dx);
+        }
+        let anim = if self.distance_to(player) < ATTACK_RANGE && self.state == EnemyState::Chasing {
+            EnemyAnim::Attack
+        } else if moved {
+            EnemyAnim::Walk
+        } else {
+            EnemyAnim::Idle
+        };
+        self.set_anim(anim);
+
+        match &mut self.sprite {
+            Some(sprite) => sprite.update(dt),
+            None => Vec::new(),
+        }
+    }
+
+    // Al ver u oir al jugador pasa a persecucion, se gira hacia el y recuerda
+    // su celda; lo deja al pasar `GIVE_UP_TIME` sin notarlo. Devuelve si lo ve.
+    fn watch(&mut self, map: &Map, player: &Player, dt: f64) -> bool {
+        let sees_player = self.distance_to(player) < SIGHT_RANGE
+            && pathfinding::has_line_of_sight(map, (self.x, self.y), (player.x, player.y));
+        // Los pasos se oyen a traves de las paredes; agachado casi no suenan
+        let hears_player = self.distance_to(player) < player.noise();
+
+        if sees_player || hears_player {
+            self.state = EnemyState::Chasing;
+            self.time_since_seen = 0.0;
+            self.facing = (player.y - self.y).atan2(player.x - self.x);
+            self.target = (player.x as usize, player.y as usize);
+        } else {
+            self.time_since_seen += dt;
+            if self.state == EnemyState::Chasing && self.time_since_seen > GIVE_UP_TIME {
+                self.state = EnemyState::Idle;
+                self.path.clear();
+            }
+        }
+        sees_player
+    }
+
+    // Sigue la ultima posicion conocida del jugador por el laberinto
+    fn pursue(&mut self, map: &Map, player: &Player, sees_player: bool, dt: f64) {
+        self.repath_timer -= dt;
+        if self.repath_timer <= 0.0 || self.path.is_empty() {
+            let cell = (self.x as usize, self.y as usize);
+            self.path = pathfinding::find_path(map, cell, self.target).unwrap_or_default();
+            self.repath_timer = REPATH_INTERVAL;
+        }
+
+        let mut step = self.speed * dt;
+        while step > 0.0 {
+            let (goal_x, goal_y) = match self.path.first() {
+                Some(&(cx, cy)) => (cx as f64 + 0.5, cy as f64 + 0.5),
+                None if sees_player => (player.x, player.y),
+                None => break,
+            };
+            let dx = goal_x - self.x;
+            let dy = goal_y - self.y;
+            let distance = (dx * dx + dy * dy).sqrt();
+            if distance <= step {
+                self.x = goal_x;
+                self.y = goal_y;
+                step -= distance;
+                if self.path.is_empty() {
+                    break;
                 }
+                self.path.remove(0);
+            } else {
+                self.x += dx / distance * step;
+                self.y += dy / distance * step;
+                break;
             }
         }
     }
-}    
\ No newline at end of file
+
+    pub fn distance_to(&self, player: &Player) -> f64 {
+        ((self.x - player.x).powi(2) + (self.y - player.y).powi(2)).sqrt()
+    }
+
+    // Dano que hace al jugador en este cuadro si lo esta tocando y ya paso
+    // el tiempo desde el ultimo golpe
+    pub fn attack(&mut self, player: &Player) -> Option<f64> {
+        if self.attack_timer > 0.0 || self.distance_to(player) >= CONTACT_RANGE {
+            return None;
+        }
+        self.attack_timer = ATTACK_COOLDOWN;
+        Some(ATTACK_DAMAGE)
+    }
+    
+    // Cuadro del atlas que corresponde a la animacion actual vista desde el jugador
+    pub fn sprite_frame(&self, player: &Player) -> Option<&RgbaImage> {
+        let sprite = self.sprite.as_ref()?;
+        Some(sprite.frame_facing(self.view_direction(player, sprite.directions())))
+    }
+
+    // Cual de los `directions` angulos del atlas ve la camara: 0 si el enemigo
+    // mira hacia el jugador, creciendo con el angulo entre ambos.
+    fn view_direction(&self, player: &Player, directions: usize) -> usize {
+        let to_camera = (player.y - self.y).atan2(player.x - self.x);
+        let relative = (to_camera - self.facing).rem_euclid(2.0 * PI);
+        let step = 2.0 * PI / directions as f64;
+        (relative / step).round() as usize % directions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::BASE_DIR;
+    use crate::movement::MoveMode;
+
+    // Direccion del atlas que ve una camara ubicada a `degrees` del frente del enemigo
+    fn direction_seen_from(enemy: &Enemy, degrees: f64, directions: usize) -> usize {
+        let angle = enemy.facing + degrees.to_radians();
+        let player = Player::at(enemy.x + 3.0 * angle.cos(), enemy.y + 3.0 * angle.sin());
+        enemy.view_direction(&player, directions)
+    }
+
+    #[test]
+    fn view_direction_switches_halfway_between_angles() {
+        let mut enemy = Enemy::at(5.5, 5.5, &Assets::new(BASE_DIR));
+        for facing in [0.0, 1.0, -2.5] {
+            enemy.facing = facing;
+            assert_eq!(direction_seen_from(&enemy, 0.0, 8), 0);
+            assert_eq!(direction_seen_from(&enemy, 22.4, 8), 0);
+            assert_eq!(direction_seen_from(&enemy, 22.6, 8), 1);
+            assert_eq!(direction_seen_from(&enemy, 45.0, 8), 1);
+            assert_eq!(direction_seen_from(&enemy, 180.0, 8), 4);
+            assert_eq!(direction_seen_from(&enemy, 337.4, 8), 7);
+            // Pasando la mitad del ultimo angulo vuelve al frente
+            assert_eq!(direction_seen_from(&enemy, 337.6, 8), 0);
+            assert_eq!(direction_seen_from(&enemy, -22.4, 8), 0);
+        }
+    }
+
+    #[test]
+    fn chases_the_player_around_corners_after_seeing_them() {
+        let map = Map::parse("+-----+\n|E....|\n+---+ |\n|     |\n+-----+\n");
+        let assets = Assets::new(BASE_DIR);
+        let mut enemy = Enemy::at(1.5, 1.5, &assets);
+        let mut player = Player::at(4.5, 1.5);
+        enemy.update(&map, &player, 0.1);
+        assert_eq!(enemy.state, EnemyState::Chasing);
+
+        // El jugador dobla la esquina: el enemigo va a su ultima celda conocida
+        player.x = 1.5;
+        player.y = 3.5;
+        for _ in 0..40 {
+            enemy.update(&map, &player, 0.1);
+        }
+        assert_eq!((enemy.x as usize, enemy.y as usize), (4, 1));
+        assert!(enemy.path.is_empty());
+
+        // Sin volver a verlo se rinde y deja de moverse
+        for _ in 0..50 {
+            enemy.update(&map, &player, 0.1);
+        }
+        assert_eq!(enemy.state, EnemyState::Idle);
+    }
+
+    #[test]
+    fn hears_sprinting_through_walls_but_not_crouching() {
+        let map = Map::parse("+-----+\n|E    |\n+---- |\n|     |\n+-----+\n");
+        let assets = Assets::new(BASE_DIR);
+        let mut player = Player::at(1.5, 3.5);
+        player.movement.mode = MoveMode::Crouch;
+        player.movement.velocity = (MoveMode::Crouch.max_speed(), 0.0);
+        let mut enemy = Enemy::at(1.5, 1.5, &assets);
+        enemy.update(&map, &player, 0.1);
+        assert_eq!(enemy.state, EnemyState::Idle);
+
+        player.movement.mode = MoveMode::Sprint;
+        player.movement.velocity = (MoveMode::Sprint.max_speed(), 0.0);
+        enemy.update(&map, &player, 0.1);
+        assert_eq!(enemy.state, EnemyState::Chasing);
+        assert_eq!(enemy.target, (1, 3));
+    }
+
+    #[test]
+    fn single_direction_atlas_always_uses_the_front() {
+        let enemy = Enemy::at(5.5, 5.5, &Assets::new(BASE_DIR));
+        for degrees in [0.0, 90.0, 179.0, 270.0] {
+            assert_eq!(direction_seen_from(&enemy, degrees, 1), 0);
+        }
+    }
+}
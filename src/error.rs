@@ -0,0 +1,60 @@
+use std::fmt;
+use std::io;
+
+// Lo que puede fallar al cargar el juego. Cada error nombra el archivo que
+// lo causo para poder mostrarlo en pantalla o al salir.
+#[derive(Debug)]
+pub enum GameError {
+    Io { file: String, error: io::Error },
+    Image { file: String, message: String },
+    Audio { file: String, message: String },
+    Map { file: String, message: String },
+    Config { file: String, message: String },
+    // Problemas que no vienen de un archivo (p. ej. no se pudo abrir la ventana)
+    Other(String),
+}
+
+impl GameError {
+    pub fn config(file: &str, message: impl fmt::Display) -> Self {
+        GameError::Config { file: file.to_string(), message: message.to_string() }
+    }
+
+    pub fn file(&self) -> Option<&str> {
+        match self {
+            GameError::Io { file, .. }
+            | GameError::Image { file, .. }
+            | GameError::Audio { file, .. }
+            | GameError::Map { file, .. }
+            | GameError::Config { file, .. } => Some(file),
+            GameError::Other(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::Io { file, error } => write!(f, "Failed to read '{}': {}", file, error),
+            GameError::Image { file, message } => write!(f, "Failed to decode image '{}': {}", file, message),
+            GameError::Audio { file, message } => write!(f, "Failed to decode sound '{}': {}", file, message),
+            GameError::Map { file, message } => write!(f, "Invalid map '{}': {}", file, message),
+            GameError::Config { file, message } => write!(f, "Invalid settings in '{}': {}", file, message),
+            GameError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+// El resto del juego reporta errores como `String`; asi `?` funciona en ambos sentidos
+impl From<GameError> for String {
+    fn from(error: GameError) -> String {
+        error.to_string()
+    }
+}
+
+impl From<String> for GameError {
+    fn from(message: String) -> GameError {
+        GameError::Other(message)
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::map::Map;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Algorithm {
+    // Pasillos largos y pocas bifurcaciones
+    Backtracker,
+    // Muchas bifurcaciones cortas
+    Prim,
+}
+
+impl Algorithm {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "backtracker" => Some(Algorithm::Backtracker),
+            "prim" => Some(Algorithm::Prim),
+            _ => None,
+        }
+    }
+}
+
+pub const ALGORITHMS: &str = "backtracker, prim";
+
+// Tamano en celdas de los laberintos de `play --seed`
+pub const SEED_SIZE: (usize, usize) = (8, 6);
+
+// Laberinto perfecto de `width`x`height` celdas con el mismo dibujo que
+// maze.txt: cada celda ocupa 3x2 caracteres y las paredes son '+', '-' y '|'.
+// El jugador empieza arriba a la izquierda, la meta queda en la celda mas
+// lejana y el enemigo a mitad de camino entre ambos.
+pub fn generate(algorithm: Algorithm, width: usize, height: usize, seed: u64) -> Map {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let passages = match algorithm {
+        Algorithm::Backtracker => backtracker(width, height, &mut rng),
+        Algorithm::Prim => prim(width, height, &mut rng),
+    };
+
+    // Texto con todas las paredes, luego se abren los pasajes
+    let mut rows: Vec<Vec<char>> = (0..height * 2 + 1)
+        .map(|y| {
+            (0..width * 3 + 1)
+                .map(|x| match (y % 2 == 0, x % 3 == 0) {
+                    (true, true) => '+',
+                    (true, false) => '-',
+                    (false, true) => '|',
+                    (false, false) => ' ',
+                })
+                .collect()
+        })
+        .collect();
+    for &((ax, ay), (bx, by)) in &passages {
+        if ax != bx {
+            rows[ay * 2 + 1][ax.max(bx) * 3] = ' ';
+        } else {
+            let y = ay.max(by) * 2;
+            rows[y][ax * 3 + 1] = ' ';
+            rows[y][ax * 3 + 2] = ' ';
+        }
+    }
+
+    let distances = distances_from((0, 0), width, height, &passages);
+    let goal = (0..width * height).max_by_key(|&i| distances[i]).unwrap_or(0);
+    let halfway = distances[goal] / 2;
+    let enemy = (0..width * height)
+        .filter(|&i| distances[i] >= halfway && i != goal)
+        .min_by_key(|&i| distances[i])
+        .unwrap_or(goal);
+    for (cell, mark) in [(0, 'p'), (goal, 'g'), (enemy, 'E')] {
+        rows[cell / width * 2 + 1][cell % width * 3 + 1] = mark;
+    }
+
+    let text: Vec<String> = rows.iter().map(|row| row.iter().collect()).collect();
+    Map::parse(&text.join("\n"))
+}
+
+type Passage = ((usize, usize), (usize, usize));
+
+fn neighbors(x: usize, y: usize, width: usize, height: usize) -> Vec<(usize, usize)> {
+    let mut cells = Vec::with_capacity(4);
+    if x > 0 {
+        cells.push((x - 1, y));
+    }
+    if x + 1 < width {
+        cells.push((x + 1, y));
+    }
+    if y > 0 {
+        cells.push((x, y - 1));
+    }
+    if y + 1 < height {
+        cells.push((x, y + 1));
+    }
+    cells
+}
+
+fn backtracker(width: usize, height: usize, rng: &mut StdRng) -> Vec<Passage> {
+    let mut visited = vec![false; width * height];
+    let mut passages = Vec::new();
+    let mut stack = vec![(0, 0)];
+    visited[0] = true;
+    while let Some(&(x, y)) = stack.last() {
+        let unvisited: Vec<(usize, usize)> = neighbors(x, y, width, height).into_iter()
+            .filter(|&(nx, ny)| !visited[ny * width + nx])
+            .collect();
+        match unvisited.choose(rng) {
+            Some(&next) => {
+                visited[next.1 * width + next.0] = true;
+                passages.push(((x, y), next));
+                stack.push(next);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+    passages
+}
+
+fn prim(width: usize, height: usize, rng: &mut StdRng) -> Vec<Passage> {
+    let mut visited = vec![false; width * height];
+    let mut passages = Vec::new();
+    let mut frontier: Vec<Passage> = neighbors(0, 0, width, height).into_iter().map(|n| ((0, 0), n)).collect();
+    visited[0] = true;
+    while !frontier.is_empty() {
+        let (from, to) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+        if visited[to.1 * width + to.0] {
+            continue;
+        }
+        visited[to.1 * width + to.0] = true;
+        passages.push((from, to));
+        for next in neighbors(to.0, to.1, width, height) {
+            if !visited[next.1 * width + next.0] {
+                frontier.push((to, next));
+            }
+        }
+    }
+    passages
+}
+
+// Pasos desde `start` hasta cada celda siguiendo los pasajes
+fn distances_from(start: (usize, usize), width: usize, height: usize, passages: &[Passage]) -> Vec<usize> {
+    let mut links = vec![Vec::new(); width * height];
+    for &((ax, ay), (bx, by)) in passages {
+        links[ay * width + ax].push(by * width + bx);
+        links[by * width + bx].push(ay * width + ax);
+    }
+    let mut distances = vec![usize::MAX; width * height];
+    let mut queue = VecDeque::from([start.1 * width + start.0]);
+    distances[start.1 * width + start.0] = 0;
+    while let Some(cell) = queue.pop_front() {
+        for &next in &links[cell] {
+            if distances[next] == usize::MAX {
+                distances[next] = distances[cell] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+pub fn from_seed(seed: u64) -> Map {
+    generate(Algorithm::Backtracker, SEED_SIZE.0, SEED_SIZE.1, seed)
+}
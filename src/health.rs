@@ -0,0 +1,70 @@
+pub const MAX_HEALTH: f64 = 100.0;
+pub const LIVES: u32 = 3;
+// Segundos sin recibir dano despues de un golpe o de reaparecer
+const INVULNERABLE_TIME: f64 = 1.2;
+// Cuanto dura el destello rojo de un golpe
+const HURT_TIME: f64 = 0.6;
+
+// Vida y vidas del jugador. Al llegar a 0 se pierde una vida y se reaparece
+// en el ultimo punto de control; sin vidas termina la partida.
+pub struct Health {
+    pub current: f64,
+    pub lives: u32,
+    invulnerable: f64,
+    hurt: f64,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Health::new()
+    }
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Health { current: MAX_HEALTH, lives: LIVES, invulnerable: 0.0, hurt: 0.0 }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.invulnerable = (self.invulnerable - dt).max(0.0);
+        self.hurt = (self.hurt - dt).max(0.0);
+    }
+
+    // Aplica el dano salvo durante la invulnerabilidad; devuelve si se aplico
+    pub fn damage(&mut self, amount: f64) -> bool {
+        if self.is_invulnerable() || self.is_dead() {
+            return false;
+        }
+        self.current = (self.current - amount).max(0.0);
+        self.invulnerable = INVULNERABLE_TIME;
+        self.hurt = HURT_TIME;
+        true
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
+    }
+
+    // Gasta una vida y recupera la salud; devuelve false si no quedaban
+    pub fn lose_life(&mut self) -> bool {
+        if self.lives == 0 {
+            return false;
+        }
+        self.lives -= 1;
+        if self.lives == 0 {
+            return false;
+        }
+        self.current = MAX_HEALTH;
+        self.invulnerable = INVULNERABLE_TIME;
+        true
+    }
+
+    // Intensidad (0..1) del destello de dano en pantalla
+    pub fn hurt_flash(&self) -> f64 {
+        self.hurt / HURT_TIME
+    }
+}
@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use proyecto1::animated_sprite::AnimatedSprite;
+use proyecto1::config::Config;
+use proyecto1::enemy::{self, Enemy};
+use proyecto1::look::LookSettings;
+use proyecto1::map::Map;
+use proyecto1::mixer::MixerSettings;
+use proyecto1::renderer::WALL_TEXTURE;
+
+use crate::display::Display;
+use crate::{level_settings, GameState, SETTINGS_FILE};
+
+// Cada cuanto se revisan las fechas de los archivos
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Que hay que recargar cuando cambia un archivo vigilado
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Reload {
+    Map,
+    Level,
+    Texture,
+    Sprites,
+    Settings,
+}
+
+impl Reload {
+    fn name(self) -> &'static str {
+        match self {
+            Reload::Map => "map",
+            Reload::Level => "level settings",
+            Reload::Texture => "wall texture",
+            Reload::Sprites => "sprites",
+            Reload::Settings => "settings",
+        }
+    }
+}
+
+// Vigila archivos revisando su fecha de modificacion (sin depender de
+// notificaciones del sistema). Un archivo que no existe tambien se vigila:
+// crearlo cuenta como cambio.
+pub struct Watcher {
+    files: Vec<(PathBuf, Reload, Option<SystemTime>)>,
+    last_poll: Instant,
+}
+
+impl Watcher {
+    pub fn new() -> Self {
+        Watcher { files: Vec::new(), last_poll: Instant::now() }
+    }
+
+    // Reemplaza la lista de archivos; los cambios se cuentan desde ahora
+    pub fn watch(&mut self, files: Vec<(PathBuf, Reload)>) {
+        self.files = files.into_iter().map(|(path, reload)| {
+            let modified = modified(&path);
+            (path, reload, modified)
+        }).collect();
+    }
+
+    // Devuelve que recargar (sin repetir) si algo cambio desde la ultima revision
+    pub fn poll(&mut self) -> Vec<Reload> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.last_poll = Instant::now();
+
+        let mut changed = Vec::new();
+        for (path, reload, last) in &mut self.files {
+            let modified = modified(path);
+            if modified != *last {
+                *last = modified;
+                if !changed.contains(reload) {
+                    changed.push(*reload);
+                }
+            }
+        }
+        changed
+    }
+}
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl GameState {
+    // Archivos de los que sale lo que esta cargado; se llama cada vez que eso cambia
+    pub fn watch_files(&mut self) {
+        let mut names = vec![
+            (self.map_file.clone(), Reload::Map),
+            (Map::level_file(&self.map_file), Reload::Level),
+            (WALL_TEXTURE.to_string(), Reload::Texture),
+        ];
+        let sprite_files = self.animated_sprite.files().iter()
+            .chain(self.enemies.iter().flat_map(|enemy| enemy.sprite_files()));
+        for file in sprite_files {
+            if !names.iter().any(|(name, _)| name == file) {
+                names.push((file.clone(), Reload::Sprites));
+            }
+        }
+
+        let mut files: Vec<(PathBuf, Reload)> = names.into_iter()
+            .filter_map(|(name, reload)| Some((self.assets.path(&name)?, reload)))
+            .collect();
+        files.push((PathBuf::from(SETTINGS_FILE), Reload::Settings));
+        self.watcher.watch(files);
+    }
+
+    // Recarga lo que cambio en disco. Un error no detiene el juego: se queda
+    // lo que ya estaba cargado y el mensaje se muestra en pantalla hasta la
+    // siguiente recarga correcta.
+    pub fn hot_reload(&mut self) {
+        let changed = self.watcher.poll();
+        if changed.is_empty() {
+            return;
+        }
+
+        let errors: Vec<String> = changed.iter()
+            .filter_map(|&reload| self.reload(reload).err())
+            .collect();
+        if errors.is_empty() {
+            let names: Vec<&str> = changed.iter().map(|reload| reload.name()).collect();
+            self.console.print(&format!("reloaded {}", names.join(", ")));
+            self.reload_error = None;
+        } else {
+            let message = format!("Reload failed: {}", errors.join("\n"));
+            println!("{}", message);
+            self.console.print(&message);
+            self.reload_error = Some(message);
+        }
+        // El atlas puede apuntar a otras imagenes despues de recargarlo
+        self.watch_files();
+    }
+
+    fn reload(&mut self, reload: Reload) -> Result<(), String> {
+        match reload {
+            Reload::Map => self.reload_map(),
+            Reload::Level => {
+                let (atmosphere, lighting) = level_settings(&self.assets, &self.map_file, &self.map)?;
+                self.renderer.atmosphere = atmosphere;
+                self.renderer.lighting = lighting;
+                Ok(())
+            }
+            Reload::Texture => Ok(self.renderer.reload_texture(&self.assets)?),
+            Reload::Sprites => {
+                let sprite = AnimatedSprite::from_atlas(&self.assets, "sprite.atlas")?;
+                let enemy_sprite = AnimatedSprite::from_atlas(&self.assets, enemy::ATLAS)?;
+                self.animated_sprite = sprite;
+                for enemy in &mut self.enemies {
+                    enemy.set_sprite(enemy_sprite.clone());
+                }
+                Ok(())
+            }
+            Reload::Settings => {
+                let settings = Config::load(SETTINGS_FILE)?;
+                self.display = Display::from_config(&settings, self.window_size)?;
+                self.audio.set_settings(MixerSettings::from_config(&settings)?);
+                self.look.settings = LookSettings::from_config(&settings)?;
+                self.renderer.camera.configure(&settings)?;
+                self.settings = settings;
+                Ok(())
+            }
+        }
+    }
+
+    // El jugador y los enemigos se quedan donde estan si siguen en un lugar
+    // valido del nuevo mapa; si no, vuelven a un inicio 'E' del nuevo mapa.
+    fn reload_map(&mut self) -> Result<(), String> {
+        let map = Map::load(&self.assets, &self.map_file)?;
+        let (atmosphere, lighting) = level_settings(&self.assets, &self.map_file, &map)?;
+
+        if !map.is_walkable(self.player.x, self.player.y) {
+            (self.player.x, self.player.y) = map.find_player_start();
+        }
+        let starts = map.enemy_starts();
+        for (i, enemy) in self.enemies.iter_mut().enumerate() {
+            if !map.is_walkable(enemy.x, enemy.y) {
+                let (x, y) = starts[i % starts.len()];
+                *enemy = Enemy::at(x, y, &self.assets);
+            }
+        }
+        self.renderer.atmosphere = atmosphere;
+        self.renderer.lighting = lighting;
+        self.map = map;
+        Ok(())
+    }
+}
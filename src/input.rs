@@ -0,0 +1,22 @@
+// Lo que el jugador pidio en un cuadro. Se separa de la ventana para poder
+// grabarlo y repetirlo igual en una repeticion.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Input {
+    pub forward: bool,
+    pub backward: bool,
+    pub left: bool,
+    pub right: bool,
+    // Giro de este cuadro en radianes (positivo = a la derecha)
+    pub turn: f64,
+    // Cuanto se inclino la vista hacia arriba en este cuadro (ver `Player::pitch`)
+    pub pitch: f64,
+    pub flashlight: bool,
+    pub sprint: bool,
+    pub crouch: bool,
+}
+
+impl Input {
+    pub fn moving(&self) -> bool {
+        self.forward || self.backward || self.left || self.right
+    }
+}
@@ -0,0 +1,31 @@
+// Motor de raycasting: mapa, camara (el jugador), render, sprites, audio y
+// texto en pantalla. No depende de la ventana (minifb), asi que las
+// herramientas y las pruebas pueden usarlo sin abrir una.
+pub mod animated_sprite;
+pub mod assets;
+pub mod atmosphere;
+pub mod audio;
+pub mod audio_backend;
+pub mod camera;
+pub mod composite;
+pub mod config;
+pub mod enemy;
+pub mod error;
+pub mod generate;
+pub mod health;
+pub mod input;
+pub mod lighting;
+pub mod look;
+pub mod map;
+pub mod mixer;
+pub mod movement;
+pub mod pack;
+pub mod pathfinding;
+pub mod platform;
+pub mod player;
+pub mod renderer;
+pub mod replay;
+pub mod spatial;
+pub mod synth;
+pub mod tension;
+pub mod ui;
@@ -0,0 +1,252 @@
+use crate::config::Config;
+use crate::map::Map;
+use crate::pathfinding;
+
+// Muestras del lightmap por celda del mapa (en cada eje)
+const LIGHTMAP_RESOLUTION: usize = 4;
+
+const FLASHLIGHT_RANGE: f64 = 7.0;
+// Medio angulo del cono; el borde se suaviza desde FLASHLIGHT_SOFT_EDGE
+const FLASHLIGHT_HALF_ANGLE: f64 = 0.4;
+const FLASHLIGHT_SOFT_EDGE: f64 = 0.25;
+const FLASHLIGHT_COLOR: [f64; 3] = [1.0, 0.95, 0.8];
+// Fraccion de bateria por segundo encendida (dura dos minutos)
+const BATTERY_DRAIN: f64 = 1.0 / 120.0;
+// Por debajo de este nivel la linterna se apaga poco a poco
+pub const BATTERY_LOW: f64 = 0.15;
+
+// Luz puntual colocada por el nivel, en [light.NOMBRE] del .cfg del mapa:
+//
+//   [light.antorcha]
+//   x = 3.5                   # posicion en celdas
+//   y = 1.5
+//   color = "#ffb060"
+//   radius = 4.0
+//   intensity = 1.0
+//   flicker = 0.3             # 0 = fija; las fijas se precalculan
+#[derive(Clone, Copy, Debug)]
+pub struct Light {
+    pub x: f64,
+    pub y: f64,
+    pub color: [f64; 3],
+    pub radius: f64,
+    pub intensity: f64,
+    pub flicker: f64,
+}
+
+impl Light {
+    fn from_config(config: &Config, section: &str) -> Result<Self, String> {
+        let x = config.get_f64(section, "x")?.ok_or_else(|| format!("[{}] needs 'x'", section))?;
+        let y = config.get_f64(section, "y")?.ok_or_else(|| format!("[{}] needs 'y'", section))?;
+        let color = config.get_color(section, "color")?.unwrap_or(0xFFFFFF);
+        let radius = config.get_f64(section, "radius")?.unwrap_or(4.0);
+        if radius <= 0.0 {
+            return Err(format!("[{}] radius must be positive", section));
+        }
+        Ok(Light {
+            x,
+            y,
+            color: [
+                ((color >> 16) & 0xFF) as f64 / 255.0,
+                ((color >> 8) & 0xFF) as f64 / 255.0,
+                (color & 0xFF) as f64 / 255.0,
+            ],
+            radius,
+            intensity: config.get_f64(section, "intensity")?.unwrap_or(1.0).max(0.0),
+            flicker: config.get_f64(section, "flicker")?.unwrap_or(0.0).clamp(0.0, 1.0),
+        })
+    }
+
+    // Factor de parpadeo en el tiempo `t`; cada luz usa su posicion como semilla
+    fn flicker_factor(&self, t: f64) -> f64 {
+        if self.flicker == 0.0 {
+            return 1.0;
+        }
+        let seed = self.x * 12.9898 + self.y * 78.233;
+        let noise = (t * 7.3 + seed).sin() * (t * 13.1 + seed * 2.1).sin() * 0.5 + 0.5;
+        1.0 - self.flicker * noise
+    }
+}
+
+// Luz de las fuentes del nivel. Las luces fijas se suman en un solo lightmap al
+// cargar el nivel (con sombras de las paredes); cada luz que parpadea tiene su
+// propio lightmap, que se escala por su parpadeo en cada cuadro.
+pub struct Lighting {
+    width: usize,
+    height: usize,
+    open: Vec<bool>,
+    static_map: Vec<[f32; 3]>,
+    flickering: Vec<(Light, Vec<[f32; 3]>)>,
+    current: Vec<[f32; 3]>,
+    time: f64,
+}
+
+impl Lighting {
+    pub fn from_config(config: &Config, map: &Map) -> Result<Self, String> {
+        let mut lights = Vec::new();
+        for section in config.section_names() {
+            if section.starts_with("light.") {
+                lights.push(Light::from_config(config, section)?);
+            }
+        }
+
+        let width = map.width() * LIGHTMAP_RESOLUTION;
+        let height = map.height() * LIGHTMAP_RESOLUTION;
+        let open = (0..width * height)
+            .map(|i| {
+                let (x, y) = sample_position(i % width, i / width);
+                !map.is_wall(x, y)
+            })
+            .collect::<Vec<_>>();
+
+        let mut static_map = vec![[0.0; 3]; width * height];
+        let mut flickering = Vec::new();
+        for light in lights {
+            let layer = bake(map, &light, width, height, &open);
+            if light.flicker > 0.0 {
+                flickering.push((light, layer));
+            } else {
+                for (total, sample) in static_map.iter_mut().zip(&layer) {
+                    for c in 0..3 {
+                        total[c] += sample[c];
+                    }
+                }
+            }
+        }
+
+        let current = static_map.clone();
+        Ok(Lighting { width, height, open, static_map, flickering, current, time: 0.0 })
+    }
+
+    // Sin luces: todo queda a la luz ambiente
+    pub fn empty(map: &Map) -> Self {
+        Lighting::from_config(&Config::new(), map).unwrap()
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.time += dt;
+        if self.flickering.is_empty() {
+            return;
+        }
+        self.current.copy_from_slice(&self.static_map);
+        for (light, layer) in &self.flickering {
+            let factor = light.flicker_factor(self.time) as f32;
+            for (total, sample) in self.current.iter_mut().zip(layer) {
+                for c in 0..3 {
+                    total[c] += sample[c] * factor;
+                }
+            }
+        }
+    }
+
+    // Luz en un punto del mundo, interpolada entre las muestras abiertas vecinas
+    pub fn sample(&self, x: f64, y: f64) -> [f64; 3] {
+        let gx = x * LIGHTMAP_RESOLUTION as f64 - 0.5;
+        let gy = y * LIGHTMAP_RESOLUTION as f64 - 0.5;
+        let x0 = gx.floor();
+        let y0 = gy.floor();
+        let tx = gx - x0;
+        let ty = gy - y0;
+
+        let mut light = [0.0; 3];
+        let mut total_weight = 0.0;
+        for (dx, dy, weight) in [(0, 0, (1.0 - tx) * (1.0 - ty)), (1, 0, tx * (1.0 - ty)), (0, 1, (1.0 - tx) * ty), (1, 1, tx * ty)] {
+            let sx = x0 as i64 + dx;
+            let sy = y0 as i64 + dy;
+            if sx < 0 || sy < 0 || sx >= self.width as i64 || sy >= self.height as i64 {
+                continue;
+            }
+            let index = sy as usize * self.width + sx as usize;
+            // Las muestras dentro de paredes no cuentan, asi los bordes no se oscurecen
+            if !self.open[index] {
+                continue;
+            }
+            for (total, sample) in light.iter_mut().zip(self.current[index]) {
+                *total += sample as f64 * weight;
+            }
+            total_weight += weight;
+        }
+        if total_weight > 0.0 {
+            for c in light.iter_mut() {
+                *c /= total_weight;
+            }
+        }
+        light
+    }
+}
+
+fn sample_position(sx: usize, sy: usize) -> (f64, f64) {
+    (
+        (sx as f64 + 0.5) / LIGHTMAP_RESOLUTION as f64,
+        (sy as f64 + 0.5) / LIGHTMAP_RESOLUTION as f64,
+    )
+}
+
+fn bake(map: &Map, light: &Light, width: usize, height: usize, open: &[bool]) -> Vec<[f32; 3]> {
+    let mut layer = vec![[0.0; 3]; width * height];
+    for sy in 0..height {
+        for sx in 0..width {
+            let index = sy * width + sx;
+            if !open[index] {
+                continue;
+            }
+            let (x, y) = sample_position(sx, sy);
+            let distance = ((x - light.x).powi(2) + (y - light.y).powi(2)).sqrt();
+            if distance >= light.radius || !pathfinding::has_line_of_sight(map, (light.x, light.y), (x, y)) {
+                continue;
+            }
+            let falloff = (1.0 - distance / light.radius).powi(2) * light.intensity;
+            layer[index] = light.color.map(|c| (c * falloff) as f32);
+        }
+    }
+    layer
+}
+
+// Linterna del jugador: un cono en la direccion de `Player::angle`
+pub struct Flashlight {
+    pub on: bool,
+    pub battery: f64,
+}
+
+impl Default for Flashlight {
+    fn default() -> Self {
+        Flashlight::new()
+    }
+}
+
+impl Flashlight {
+    pub fn new() -> Self {
+        Flashlight { on: true, battery: 1.0 }
+    }
+
+    pub fn toggle(&mut self) {
+        self.on = !self.on;
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        if self.on {
+            self.battery = (self.battery - BATTERY_DRAIN * dt).max(0.0);
+        }
+    }
+
+    fn intensity(&self) -> f64 {
+        if !self.on {
+            0.0
+        } else {
+            (self.battery / BATTERY_LOW).min(1.0)
+        }
+    }
+
+    // Luz que aporta a un punto a `distance` y `offset` radianes del centro del cono
+    pub fn light_at(&self, offset: f64, distance: f64) -> [f64; 3] {
+        let intensity = self.intensity();
+        let offset = offset.abs();
+        if intensity == 0.0 || offset >= FLASHLIGHT_HALF_ANGLE || distance >= FLASHLIGHT_RANGE {
+            return [0.0; 3];
+        }
+        let edge = ((FLASHLIGHT_HALF_ANGLE - offset) / (FLASHLIGHT_HALF_ANGLE - FLASHLIGHT_SOFT_EDGE)).min(1.0);
+        let falloff = (1.0 - distance / FLASHLIGHT_RANGE).powi(2);
+        let amount = intensity * edge * falloff;
+        FLASHLIGHT_COLOR.map(|c| c * amount)
+    }
+}
@@ -0,0 +1,122 @@
+use crate::config::Config;
+
+// Segundos que tarda el giro con teclado en llegar a `key_speed`; un toque
+// corto gira poco para poder apuntar fino
+const KEY_RAMP: f64 = 0.25;
+
+// Como se gira la camara con el mouse y el teclado. Se configura en [mouse]
+// de settings.cfg:
+//
+//   [mouse]
+//   sensitivity = 0.003    # radianes por pixel
+//   acceleration = 0.5     # 0 = sin aceleracion; mas alto = gira mas rapido al mover rapido
+//   smoothing = 0.3        # 0..0.95, cuanto del giro anterior se mantiene
+//   capture = true         # retiene y oculta el cursor mientras se juega (solo
+//                          # con X11; si no, el giro se corta en el borde)
+//   key_speed = 600        # pixeles por segundo que equivalen a las flechas / Q y E
+//   invert_y = false       # mouse hacia arriba mira hacia abajo
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LookSettings {
+    pub sensitivity: f64,
+    pub acceleration: f64,
+    pub smoothing: f64,
+    pub capture: bool,
+    pub key_speed: f64,
+    pub invert_y: bool,
+}
+
+impl Default for LookSettings {
+    fn default() -> Self {
+        LookSettings::new()
+    }
+}
+
+impl LookSettings {
+    pub fn new() -> Self {
+        LookSettings { sensitivity: 0.003, acceleration: 0.0, smoothing: 0.0, capture: true, key_speed: 600.0, invert_y: false }
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let mut settings = LookSettings::new();
+        if let Some(sensitivity) = config.get_f64("mouse", "sensitivity")? {
+            if sensitivity <= 0.0 {
+                return Err("[mouse] sensitivity must be positive".to_string());
+            }
+            settings.sensitivity = sensitivity;
+        }
+        if let Some(acceleration) = config.get_f64("mouse", "acceleration")? {
+            settings.acceleration = acceleration.max(0.0);
+        }
+        if let Some(smoothing) = config.get_f64("mouse", "smoothing")? {
+            settings.smoothing = smoothing.clamp(0.0, 0.95);
+        }
+        if let Some(capture) = config.get_bool("mouse", "capture")? {
+            settings.capture = capture;
+        }
+        if let Some(key_speed) = config.get_f64("mouse", "key_speed")? {
+            settings.key_speed = key_speed.max(0.0);
+        }
+        if let Some(invert_y) = config.get_bool("mouse", "invert_y")? {
+            settings.invert_y = invert_y;
+        }
+        Ok(settings)
+    }
+
+    // Radianes por segundo para una velocidad en pixeles por segundo. Con
+    // aceleracion cada pixel gira mas cuanto mas rapido se mueve el mouse.
+    pub fn curve(&self, pixels_per_second: f64) -> f64 {
+        let boost = 1.0 + self.acceleration * pixels_per_second.abs() / 1000.0;
+        pixels_per_second * self.sensitivity * boost
+    }
+}
+
+// Giro relativo: el mouse aporta su movimiento del cuadro y las teclas de giro
+// una velocidad equivalente en pixeles, y ambos pasan por la misma curva.
+pub struct MouseLook {
+    pub settings: LookSettings,
+    // Radianes por segundo despues del suavizado
+    rate: f64,
+    // Cuanto lleva apretada una tecla de giro
+    key_held: f64,
+}
+
+impl MouseLook {
+    pub fn new(settings: LookSettings) -> Self {
+        MouseLook { settings, rate: 0.0, key_held: 0.0 }
+    }
+
+    // Radianes a girar en este cuadro. `mouse_dx` en pixeles desde el cuadro
+    // anterior; `keys` es -1 (izquierda), 0 o 1 (derecha).
+    pub fn turn(&mut self, mouse_dx: f64, keys: f64, dt: f64) -> f64 {
+        if dt <= 0.0 {
+            return 0.0;
+        }
+        let key_speed = if keys != 0.0 {
+            self.key_held += dt;
+            keys.signum() * self.settings.key_speed * (self.key_held / KEY_RAMP).min(1.0)
+        } else {
+            self.key_held = 0.0;
+            0.0
+        };
+
+        let target = self.settings.curve(mouse_dx / dt + key_speed);
+        // El suavizado no depende de los cuadros por segundo
+        let keep = self.settings.smoothing.powf(dt * 60.0);
+        self.rate = self.rate * keep + target * (1.0 - keep);
+        self.rate * dt
+    }
+
+    // Inclinacion vertical de este cuadro para `mouse_dy` pixeles (hacia
+    // abajo en la ventana es positivo). Sin aceleracion ni suavizado: la
+    // vista vertical se usa para apuntar, no para girar rapido.
+    pub fn pitch(&self, mouse_dy: f64) -> f64 {
+        let direction = if self.settings.invert_y { 1.0 } else { -1.0 };
+        mouse_dy * self.settings.sensitivity * direction
+    }
+
+    // Sin arrastrar giro de antes (p. ej. al cerrar la consola o volver de un menu)
+    pub fn reset(&mut self) {
+        self.rate = 0.0;
+        self.key_held = 0.0;
+    }
+}
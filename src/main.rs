@@ -1,134 +1,424 @@
-mod player;
-mod map;
-mod renderer;
-mod audio;
-mod ui;
-mod animated_sprite;
-mod enemy;
-
-use minifb::{Key, Window, WindowOptions};
+mod bench;
+mod display;
+mod debug;
+mod console;
+mod commands;
+mod hot_reload;
+mod editor;
+mod cli;
+mod window;
+
+use std::f64::consts::PI;
+use std::path::PathBuf;
 use std::time::{Instant, Duration};
 
-use player::Player;
-use map::Map;
-use renderer::Renderer;
-use audio::AudioManager;
-use ui::UI;
-use enemy::Enemy;
-use animated_sprite::AnimatedSprite;
+use proyecto1::{audio_backend, generate};
+use proyecto1::player::Player;
+use proyecto1::map::Map;
+use proyecto1::renderer::Renderer;
+use proyecto1::audio::AudioManager;
+use proyecto1::audio_backend::{AudioBackend, CueLog, NullBackend, RecordingBackend};
+use proyecto1::ui::UI;
+use proyecto1::enemy::Enemy;
+use proyecto1::animated_sprite::AnimatedSprite;
+use proyecto1::tension::{Tension, TensionLevel};
+use proyecto1::assets::{Assets, BASE_DIR, PACKS_DIR};
+use proyecto1::pack::Pack;
+use proyecto1::config::Config;
+use proyecto1::movement::MoveMode;
+use proyecto1::mixer::{MixerSettings, BUSES};
+use proyecto1::atmosphere::Atmosphere;
+use proyecto1::lighting::Lighting;
+use proyecto1::look::{LookSettings, MouseLook};
+use proyecto1::input::Input;
+use proyecto1::platform::{Headless, Key, KeyRepeat, Platform, Script};
+use proyecto1::replay::{Playback, Recording};
+use proyecto1::error::GameError;
+
+use display::Display;
+use debug::DebugOverlay;
+use console::{Console, Registry};
+use hot_reload::Watcher;
+use editor::Editor;
+use cli::{Command, PlayOptions};
+use window::MinifbPlatform;
 
 const WIDTH: usize = 840;
 const HEIGHT: usize = 580;
 const TARGET_FPS: u32 = 15;
+// Velocidad de giro (radianes por segundo) con la que un golpe voltea la vista
+const HIT_SPIN: f64 = 0.6;
+// A menos de esta distancia de un enemigo la camara tiembla
+const SHAKE_DISTANCE: f64 = 2.5;
+// Segundos entre ruidos del enemigo
+const ENEMY_NOISE_INTERVAL: f64 = 3.0;
+// Segundos que los pasos siguen sonando despues de soltar la tecla
+const STEP_LINGER: f64 = 0.2;
+const SETTINGS_FILE: &str = "settings.cfg";
+const MAP_FILE: &str = "maze.txt";
 
 pub struct GameState {
+    assets: Assets,
+    settings: Config,
     map: Map,
     player: Player,
-    enemy: Enemy,
+    enemies: Vec<Enemy>,
+    tension: Tension,
     renderer: Renderer,
+    display: Display,
     audio: AudioManager,
     ui: UI,
     current_state: State,
     last_frame_time: Instant,
     fps_counter: u32,
     fps: u32,
-    last_step_time: Instant,
+    // Tiempo de juego (con timescale y dt fijo) desde la ultima tecla de
+    // movimiento y desde el ultimo ruido de los enemigos
+    since_step: f64,
+    since_enemy_noise: f64,
+    last_audio_update: Instant,
     victory_sound_played: bool,
     game_over_sound_played: bool,
     animated_sprite: AnimatedSprite,
+    debug: DebugOverlay,
+    look: MouseLook,
+    cursor_grabbed: bool,
+    console: Console,
+    commands: Registry,
+    // Scripts que se estan ejecutando, para cortar un `exec` que se llama a si mismo
+    running_scripts: Vec<PathBuf>,
+    // Trucos de la consola: el enemigo no atrapa al jugador / velocidad del tiempo
+    god: bool,
+    time_scale: f64,
+    map_file: String,
+    // Archivo pendiente del comando `screenshot`
+    screenshot: Option<String>,
+    watcher: Watcher,
+    // Error de la ultima recarga en caliente, visible hasta que una recarga funcione
+    reload_error: Option<String>,
+    // Con el editor abierto, `Playing` es la vista previa del mapa que se edita
+    editor: Option<Editor>,
+    // Partida que se esta grabando (y a donde se guarda) o que se esta repitiendo
+    recording: Option<(Recording, String)>,
+    playback: Option<Playback>,
+    seed: Option<u64>,
+    window_size: (usize, usize),
+    // Sin ventana cada cuadro avanza este dt, asi la sesion no depende del reloj
+    fixed_dt: Option<f64>,
+    options: OptionsMenu,
 }
 
 enum State {
     Welcome,
+    Options,
     Playing,
+    Editor,
     Victory,
     GameOver,
 }
 
+// Lista de paquetes de recursos en la pantalla de opciones
+struct OptionsMenu {
+    available: Vec<Pack>,
+    selected: Vec<String>,
+    message: Option<String>,
+    bus: usize,
+}
+
 impl GameState {
-    pub fn new() -> Self {
-        let map = Map::load("assets/maze.txt");
+    // `map_file` es de donde salio `map` (o su nombre si se genero)
+    pub fn new(assets: Assets, settings: Config, audio_backend: Box<dyn AudioBackend>, map: Map, map_file: &str, window_size: (usize, usize)) -> Result<Self, GameError> {
         let player = Player::new(&map);
-        let enemy = Enemy::new(&map);
-        let renderer = Renderer::new(WIDTH, HEIGHT);
-        let audio = AudioManager::new();
-        let ui = UI::new();
-        let animated_sprite = AnimatedSprite::new("assets/sprite", 5, Duration::from_millis(200))
-            .expect("Failed to create animated sprite");
-
-        GameState {
+        let enemies = Enemy::spawn_all(&map, &assets);
+        let (atmosphere, lighting) = load_level(&assets, map_file, &map);
+        let display = load_display(&settings, window_size);
+        let (width, height) = display.render_size(window_size);
+        let mut renderer = Renderer::new(width, height, &assets, atmosphere, lighting)?;
+        if let Err(e) = renderer.camera.configure(&settings) {
+            println!("Invalid camera settings, using defaults: {}", e);
+        }
+        let mixer_settings = MixerSettings::from_config(&settings).unwrap_or_else(|e| {
+            println!("Invalid audio settings, using defaults: {}", e);
+            MixerSettings::new()
+        });
+        let audio = AudioManager::new(audio_backend, &assets, mixer_settings)?;
+        let look_settings = LookSettings::from_config(&settings).unwrap_or_else(|e| {
+            println!("Invalid mouse settings, using defaults: {}", e);
+            LookSettings::new()
+        });
+        let ui = UI::new(&assets)?;
+        let animated_sprite = AnimatedSprite::from_atlas(&assets, "sprite.atlas")?;
+        let options = OptionsMenu {
+            available: Vec::new(),
+            selected: assets.active_packs().map(|p| p.id.clone()).collect(),
+            message: None,
+            bus: 0,
+        };
+        let mut commands = Registry::new();
+        commands::register_builtin(&mut commands);
+        debug::register_commands(&mut commands);
+
+        let mut state = GameState {
+            assets,
+            settings,
             map,
             player,
-            enemy,
+            enemies,
+            tension: Tension::new(),
             renderer,
+            display,
             audio,
             ui,
             current_state: State::Welcome,
             last_frame_time: Instant::now(),
             fps_counter: 15,
             fps: 15,
-            last_step_time: Instant::now(),
+            since_step: STEP_LINGER,
+            since_enemy_noise: 0.0,
+            last_audio_update: Instant::now(),
             victory_sound_played: false,
             game_over_sound_played: false,
             animated_sprite,
+            debug: DebugOverlay::new(),
+            look: MouseLook::new(look_settings),
+            cursor_grabbed: false,
+            console: Console::new(),
+            running_scripts: Vec::new(),
+            commands,
+            god: false,
+            time_scale: 1.0,
+            map_file: map_file.to_string(),
+            screenshot: None,
+            watcher: Watcher::new(),
+            reload_error: None,
+            editor: None,
+            recording: None,
+            playback: None,
+            seed: None,
+            window_size,
+            fixed_dt: None,
+            options,
+        };
+        state.watch_files();
+        Ok(state)
+    }
+
+    // El movimiento sale de `input` (del teclado o de una repeticion); las
+    // teclas que no cambian la partida se leen directo de la ventana
+    fn handle_input(&mut self, platform: &dyn Platform, input: Input, dt: f64) {
+        let start = (self.player.x, self.player.y);
+        let forward = input.forward as i32 - input.backward as i32;
+        let strafe = input.right as i32 - input.left as i32;
+        self.player.walk(&self.map, forward as f64, strafe as f64, input.sprint, input.crouch, dt);
+        self.player.rotate(input.turn);
+        self.player.look_up(input.pitch);
+
+        // El balanceo sigue lo que de verdad se avanzo (no hay balanceo contra una pared)
+        let moved = ((self.player.x - start.0).powi(2) + (self.player.y - start.1).powi(2)).sqrt();
+        let speed = if dt > 0.0 { moved / dt } else { 0.0 };
+        self.renderer.camera.update(speed, dt);
+
+        // Los pasos siguen sonando un momento despues de soltar la tecla
+        self.since_step = if input.moving() { 0.0 } else { self.since_step + dt };
+        let walking = self.since_step < STEP_LINGER;
+        // Agachado los pasos no suenan
+        self.audio.set_footsteps(walking && self.player.movement.mode != MoveMode::Crouch);
+        if self.animated_sprite.has_clip("walk") {
+            self.animated_sprite.play(if walking { "walk" } else { "idle" });
+        }
+
+        if input.flashlight {
+            self.player.flashlight.toggle();
+        }
+        if self.console.open {
+            return;
+        }
+        if platform.is_key_pressed(Key::F3, KeyRepeat::No) {
+            self.debug.visible = !self.debug.visible;
+        }
+        if self.is_previewing() && platform.is_key_pressed(Key::Tab, KeyRepeat::No) {
+            self.return_to_editor(None);
         }
     }
 
-    fn handle_input(&mut self, window: &mut Window, dt: f64) {
-        let move_speed = 2.0 * dt;
+    // El motor no conoce la ventana: aqui se traducen teclado y mouse a
+    // `Input`. Las flechas y Q/E giran como si el mouse se moviera a
+    // `key_speed` pixeles por segundo.
+    fn read_input(&mut self, platform: &dyn Platform, dt: f64) -> Input {
+        let turn_keys: f64 = [(Key::Left, -1.0), (Key::Q, -1.0), (Key::Right, 1.0), (Key::E, 1.0)].iter()
+            .filter(|(key, _)| platform.is_key_down(*key))
+            .map(|(_, direction)| direction)
+            .sum();
+        let (mouse_dx, mouse_dy) = platform.mouse_delta();
+        Input {
+            forward: platform.is_key_down(Key::W),
+            backward: platform.is_key_down(Key::S),
+            left: platform.is_key_down(Key::A),
+            right: platform.is_key_down(Key::D),
+            turn: self.look.turn(mouse_dx as f64, turn_keys, dt),
+            pitch: self.look.pitch(mouse_dy as f64),
+            flashlight: platform.is_key_pressed(Key::F, KeyRepeat::No),
+            sprint: platform.is_key_down(Key::LeftShift) || platform.is_key_down(Key::RightShift),
+            crouch: platform.is_key_down(Key::LeftCtrl) || platform.is_key_down(Key::RightCtrl),
+        }
+    }
 
-        if window.is_key_down(Key::W) {
-            self.player.move_forward(&self.map, move_speed);
+    // El cursor se retiene solo mientras se juega (con [mouse] capture); en
+    // los menus, la consola y el editor hace falta verlo
+    fn update_cursor(&mut self, platform: &mut dyn Platform) {
+        let grab = self.look.settings.capture && matches!(self.current_state, State::Playing) && !self.console.open;
+        if grab != self.cursor_grabbed {
+            platform.set_cursor_grab(grab);
+            self.cursor_grabbed = grab;
+            self.look.reset();
         }
-        if window.is_key_down(Key::S) {
-            self.player.move_backward(&self.map, move_speed);
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.renderer.lighting.update(dt);
+        self.player.flashlight.update(dt);
+        self.player.health.update(dt);
+        self.player.update(dt);
+        self.player.touch_checkpoint(&self.map);
+
+        // Los eventos "cue:<nombre>" de las animaciones disparan sonidos; los
+        // de los enemigos suenan desde su posicion
+        for enemy in &mut self.enemies {
+            for event in enemy.update(&self.map, &self.player, dt) {
+                if let Some(cue) = event.name.strip_prefix("cue:") {
+                    self.audio.play_cue_at(cue, (enemy.x, enemy.y), &self.player);
+                }
+            }
         }
-        if window.is_key_down(Key::A) {
-            self.player.strafe_left(&self.map, move_speed);
+        for event in self.animated_sprite.update(dt) {
+            if let Some(cue) = event.name.strip_prefix("cue:") {
+                self.audio.play_cue(cue);
+            }
         }
-        if window.is_key_down(Key::D) {
-            self.player.strafe_right(&self.map, move_speed);
+
+        let level = self.tension.level();
+        self.tension.update(&self.enemies, &self.player, dt);
+        if self.tension.level() == TensionLevel::Chase && level != TensionLevel::Chase {
+            self.renderer.camera.shake(0.4);
         }
+        let nearest = self.enemies.iter().map(|enemy| enemy.distance_to(&self.player)).fold(f64::MAX, f64::min);
+        if nearest < SHAKE_DISTANCE {
+            self.renderer.camera.rumble(0.5 * (1.0 - nearest / SHAKE_DISTANCE));
+        }
+        self.audio.set_tension(self.tension.value(), self.tension.level());
 
-        if let Some((x, _)) = window.get_mouse_pos(minifb::MouseMode::Discard) {
-            let center_x = (self.renderer.width / 2) as f64;
-            let dx = x as f64 - center_x;
-            self.player.rotate(dx * 0.001 * dt);
+        self.since_enemy_noise += dt;
+        if self.since_enemy_noise >= ENEMY_NOISE_INTERVAL {
+            for enemy in &self.enemies {
+                self.audio.play_cue_at("sfx.enemy", (enemy.x, enemy.y), &self.player);
+            }
+            self.since_enemy_noise = 0.0;
         }
+    }
 
+    // Se llama en todos los estados para que los crossfades sigan avanzando
+    fn update_audio(&mut self) {
         let now = Instant::now();
-        if (window.is_key_down(Key::W) || window.is_key_down(Key::S) || window.is_key_down(Key::A) || window.is_key_down(Key::D)) 
-            && now.duration_since(self.last_step_time) >= Duration::from_millis(500) {
-            self.last_step_time = now;
+        let dt = now.duration_since(self.last_audio_update).as_secs_f64();
+        self.last_audio_update = now;
+        self.audio.update(dt, &self.player);
+    }
+
+    fn render(&mut self, platform: &mut dyn Platform) {
+        let (width, height) = (self.renderer.width, self.renderer.height);
+        self.renderer.render_3d(&self.map, &self.player);
+
+        let start = Instant::now();
+        let renderer = &mut self.renderer;
+        render_enemies(renderer, &self.enemies, &self.player);
+        self.animated_sprite.render(&mut renderer.frame, width, height);
+        renderer.times.sprites = start.elapsed();
+
+        let start = Instant::now();
+        renderer.render_minimap(&self.map, &self.player, &self.enemies);
+        self.ui.render_fps(self.fps, &mut renderer.frame, width, height);
+        self.ui.render_tension(self.tension.value(), &mut renderer.frame, width, height);
+        self.ui.render_battery(&self.player.flashlight, &mut renderer.frame, width, height);
+        self.ui.render_stamina(&self.player.movement, &mut renderer.frame, width, height);
+        self.ui.render_health(&self.player.health, &mut renderer.frame, width, height);
+        self.ui.render_damage(&self.player.health, &mut renderer.frame, width, height);
+        renderer.times.ui = start.elapsed();
+
+        if self.debug.visible {
+            self.debug.render(&self.ui, &mut self.renderer, &self.map, &self.player, &self.enemies);
+        }
+
+        if let Some(message) = &self.reload_error {
+            self.ui.render_error(message, &mut self.renderer.frame, width, height);
+        }
+        if let Some(filename) = self.screenshot.take() {
+            match commands::save_screenshot(&self.renderer, &filename) {
+                Ok(message) => self.console.print(&message),
+                Err(e) => self.console.print(&format!("error: {}", e)),
+            }
+        }
+        if self.console.open {
+            self.console.render(&self.ui, &mut self.renderer.frame, width, height);
         }
+
+        self.display.present(platform, &self.renderer.frame, width, height);
     }
 
-    fn update(&mut self, dt: f64) {
-        self.enemy.update(&self.map, &self.player, dt);
-        self.animated_sprite.update();
+    // Con `render_scale` la resolucion de render sigue al tamano de la ventana
+    fn sync_resolution(&mut self, platform: &dyn Platform) {
+        let (width, height) = self.display.render_size(platform.size());
+        if (width, height) != (self.renderer.width, self.renderer.height) {
+            self.renderer.resize(width, height);
+        }
     }
 
-    fn render(&mut self, window: &mut Window) {
-        let mut buffer = vec![0; self.renderer.width * self.renderer.height];
-        let mut z_buffer = vec![f64::MAX; self.renderer.width * self.renderer.height];
-    
-        self.renderer.render_3d(&self.map, &self.player, &mut buffer, &mut z_buffer);
-        self.renderer.render_minimap(&self.map, &self.player, &self.enemy, &mut buffer);
-        self.enemy.render(&mut buffer, self.renderer.width, self.renderer.height, &self.player, &z_buffer);
-        self.ui.render_fps(self.fps, &mut buffer, self.renderer.width, self.renderer.height);
-        self.animated_sprite.render(&mut buffer, self.renderer.width, self.renderer.height);
-    
-        window.update_with_buffer(&buffer, self.renderer.width, self.renderer.height).unwrap();
+    // Dibuja una pantalla fuera del juego a la resolucion de render y la muestra
+    fn present_screen(&mut self, platform: &mut dyn Platform, draw: impl FnOnce(&UI, &mut [u32], usize, usize)) {
+        let (width, height) = (self.renderer.width, self.renderer.height);
+        draw(&self.ui, &mut self.renderer.frame, width, height);
+        if let Some(message) = &self.reload_error {
+            self.ui.render_error(message, &mut self.renderer.frame, width, height);
+        }
+        self.display.present(platform, &self.renderer.frame, width, height);
     }
     
 
-    fn play(&mut self, window: &mut Window) {
+    fn play(&mut self, platform: &mut dyn Platform) {
         let frame_start = Instant::now();
-        let dt = frame_start.duration_since(self.last_frame_time).as_secs_f64();
+        let elapsed = frame_start.duration_since(self.last_frame_time).as_secs_f64();
+        let mut dt = self.fixed_dt.unwrap_or(elapsed) * self.time_scale;
+
+        if platform.is_key_pressed(Key::Backquote, KeyRepeat::No) {
+            self.toggle_console();
+        }
+        let mut commands = Vec::new();
+        if let Some(line) = self.console.handle_input(platform, &self.commands) {
+            self.audio.play_cue("ui.select");
+            commands.push(line);
+        }
+        // Con la consola abierta el teclado es para escribir, no para moverse
+        let mut input = if self.console.open { Input::default() } else { self.read_input(platform, dt) };
+        if let Some(playback) = &mut self.playback {
+            let Some(frame) = playback.next_frame() else {
+                return;
+            };
+            (dt, input) = (frame.dt, frame.input);
+            commands.extend(frame.commands.iter().cloned());
+        }
+        if let Some((recording, _)) = &mut self.recording {
+            recording.push(dt, input);
+            for line in &commands {
+                recording.push_command(line);
+            }
+        }
 
-        self.handle_input(window, dt);
+        for line in &commands {
+            self.run_command(line);
+        }
+        self.handle_input(platform, input, dt);
         self.update(dt);
-        self.render(window);
+        self.render(platform);
+        self.debug.record(dt, self.renderer.times);
 
         self.fps_counter += 1;
         if frame_start.duration_since(self.last_frame_time) >= Duration::from_secs(1) {
@@ -137,89 +427,575 @@ impl GameState {
             self.last_frame_time = frame_start;
         }
 
-        if self.map.is_player_at_goal(&self.player) {
+        if !self.god {
+            for enemy in &mut self.enemies {
+                if let Some(damage) = enemy.attack(&self.player) {
+                    if self.player.health.damage(damage) {
+                        self.renderer.camera.shake(0.6);
+                        // El golpe gira la vista hacia el enemigo
+                        let to_enemy = (enemy.y - self.player.y).atan2(enemy.x - self.player.x);
+                        let turn = (to_enemy - self.player.angle + PI).rem_euclid(2.0 * PI) - PI;
+                        self.player.spin(turn.signum() * HIT_SPIN);
+                    }
+                }
+            }
+        }
+        // Sin salud se pierde una vida; la partida termina cuando no quedan
+        let mut caught = false;
+        if self.player.health.is_dead() {
+            self.renderer.camera.shake(1.0);
+            if self.player.health.lose_life() {
+                self.respawn();
+            } else {
+                caught = true;
+            }
+        }
+        if self.is_previewing() && (caught || self.map.is_player_at_goal(&self.player)) {
+            let message = if caught { "Preview: caught by an enemy" } else { "Preview: reached the goal" };
+            self.return_to_editor(Some(message));
+        } else if self.map.is_player_at_goal(&self.player) {
             self.current_state = State::Victory;
-        } else if self.enemy.has_caught_player(&self.player) {
+            self.audio.stop_sfx();
+            self.finish_recording();
+        } else if caught {
             self.current_state = State::GameOver;
+            self.audio.stop_sfx();
+            self.finish_recording();
         }
 
         self.last_frame_time = frame_start;
     }
 
 
-    fn show_welcome_screen(&mut self, window: &mut Window) {
-        self.ui.show_welcome_screen(window);
-        if window.is_key_down(Key::Space) {
+    fn show_welcome_screen(&mut self, platform: &mut dyn Platform) {
+        self.present_screen(platform, |ui, buffer, width, height| ui.draw_welcome_screen(buffer, width, height));
+        if platform.is_key_down(Key::Space) {
             println!("Space pressed: Changing state to Playing");
-            self.current_state = State::Playing;
-            self.audio.play_background_music("assets/nobodynocrimets.mp3");
+            self.audio.play_cue("ui.select");
+            self.start_game();
+        } else if platform.is_key_pressed(Key::E, KeyRepeat::No) {
+            self.audio.play_cue("ui.select");
+            self.enter_editor();
+        } else if platform.is_key_pressed(Key::O, KeyRepeat::No) {
+            self.audio.play_cue("ui.select");
+            self.options.available = Pack::discover(PACKS_DIR);
+            self.options.message = None;
+            self.current_state = State::Options;
+        }
+    }
+
+    fn show_options_screen(&mut self, platform: &mut dyn Platform) {
+        let mut lines = vec![
+            "1-9: TOGGLE PACK   ENTER: APPLY   BACKSPACE: BACK".to_string(),
+            "UP/DOWN: BUS   LEFT/RIGHT: VOLUME   M: MUTE".to_string(),
+            String::new(),
+        ];
+        let mixer_settings = self.audio.settings();
+        for (i, bus) in BUSES.iter().enumerate() {
+            let cursor = if i == self.options.bus { ">" } else { " " };
+            let muted = if mixer_settings.is_muted(*bus) { " (MUTED)" } else { "" };
+            lines.push(format!("{} {:<7}{:>4}%{}", cursor, bus.name(), (mixer_settings.volume(*bus) * 100.0).round(), muted));
+        }
+        lines.push(String::new());
+        if self.options.available.is_empty() {
+            lines.push(format!("NO PACKS FOUND IN '{}/'", PACKS_DIR));
+        }
+        for (i, pack) in self.options.available.iter().enumerate() {
+            let mark = if self.options.selected.contains(&pack.id) { "X" } else { " " };
+            lines.push(format!("{} [{}] {} (PRIORITY {})", i + 1, mark, pack.name, pack.priority));
+        }
+        if !self.assets.conflicts().is_empty() {
+            lines.push(String::new());
+            lines.push("CONFLICTS:".to_string());
+            for conflict in self.assets.conflicts() {
+                lines.push(format!("{} FROM {} OVERRIDES {}", conflict.file, conflict.winner, conflict.overridden.join(", ")));
+            }
+        }
+        if let Some(message) = &self.options.message {
+            lines.push(String::new());
+            lines.push(message.clone());
+        }
+        self.present_screen(platform, |ui, buffer, width, _| ui.draw_text_screen(buffer, width, "RESOURCE PACKS", &lines));
+
+        let number_keys = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9];
+        for (i, key) in number_keys.iter().enumerate() {
+            if platform.is_key_pressed(*key, KeyRepeat::No) {
+                if let Some(pack) = self.options.available.get(i) {
+                    self.audio.play_cue("ui.move");
+                    match self.options.selected.iter().position(|id| id == &pack.id) {
+                        Some(index) => { self.options.selected.remove(index); }
+                        None => self.options.selected.push(pack.id.clone()),
+                    }
+                }
+            }
+        }
+
+        self.handle_mixer_keys(platform);
+
+        if platform.is_key_pressed(Key::Enter, KeyRepeat::No) {
+            self.audio.play_cue("ui.select");
+            match Assets::with_packs(BASE_DIR, PACKS_DIR, &self.options.selected) {
+                Ok(assets) => {
+                    self.options.message = Some(match self.load_assets(assets) {
+                        Ok(()) => "PACKS APPLIED".to_string(),
+                        Err(e) => e.to_string(),
+                    });
+                }
+                Err(e) => self.options.message = Some(e),
+            }
+        } else if platform.is_key_pressed(Key::Backspace, KeyRepeat::No) {
+            self.audio.play_cue("ui.select");
+            self.current_state = State::Welcome;
+        }
+    }
+
+    fn handle_mixer_keys(&mut self, platform: &dyn Platform) {
+        if platform.is_key_pressed(Key::Up, KeyRepeat::No) {
+            self.options.bus = (self.options.bus + BUSES.len() - 1) % BUSES.len();
+            self.audio.play_cue("ui.move");
+        }
+        if platform.is_key_pressed(Key::Down, KeyRepeat::No) {
+            self.options.bus = (self.options.bus + 1) % BUSES.len();
+            self.audio.play_cue("ui.move");
+        }
+
+        let bus = BUSES[self.options.bus];
+        let mut mixer_settings = self.audio.settings();
+        if platform.is_key_pressed(Key::Left, KeyRepeat::Yes) {
+            mixer_settings.set_volume(bus, mixer_settings.volume(bus) - 0.1);
+        } else if platform.is_key_pressed(Key::Right, KeyRepeat::Yes) {
+            mixer_settings.set_volume(bus, mixer_settings.volume(bus) + 0.1);
+        } else if platform.is_key_pressed(Key::M, KeyRepeat::No) {
+            mixer_settings.toggle_mute(bus);
+        } else {
+            return;
+        }
+
+        self.audio.set_settings(mixer_settings);
+        // Suena despues de aplicar el volumen para escuchar el nivel nuevo
+        self.audio.play_cue("ui.move");
+        mixer_settings.write_config(&mut self.settings);
+        if let Err(e) = self.settings.save(SETTINGS_FILE) {
+            self.options.message = Some(e);
+        }
+    }
+
+    fn outcome(&self) -> &'static str {
+        match self.current_state {
+            State::Victory => "reached the goal",
+            State::GameOver => "caught by an enemy",
+            State::Editor => "in the editor",
+            State::Welcome => "on the welcome screen",
+            _ => "still playing",
+        }
+    }
+
+    fn start_game(&mut self) {
+        self.current_state = State::Playing;
+        self.last_frame_time = Instant::now();
+        self.audio.play_cue("music.chase");
+        if self.recording.is_some() {
+            println!("Recording inputs");
         }
     }
 
-    fn show_victory_screen(&mut self, window: &mut Window) {
-        self.ui.show_victory_screen(window);
+    // Guarda la grabacion en curso; solo se graba la primera partida
+    fn finish_recording(&mut self) {
+        if let Some((recording, filename)) = self.recording.take() {
+            match recording.save(&filename) {
+                Ok(()) => println!("Saved replay to {} ({} frames)", filename, recording.frames.len()),
+                Err(e) => println!("{}", e),
+            }
+        }
+    }
+
+    // Cambia de laberinto; si el archivo no es valido se queda el actual
+    pub fn load_map(&mut self, filename: &str) -> Result<(), String> {
+        let map = Map::load(&self.assets, filename)?;
+        self.set_map(map, filename);
+        Ok(())
+    }
+
+    // `filename` es de donde se recarga y a donde guarda el editor
+    fn set_map(&mut self, map: Map, filename: &str) {
+        let (atmosphere, lighting) = load_level(&self.assets, filename, &map);
+        self.player = Player::new(&map);
+        self.enemies = Enemy::spawn_all(&map, &self.assets);
+        self.renderer.atmosphere = atmosphere;
+        self.renderer.lighting = lighting;
+        self.map = map;
+        self.map_file = filename.to_string();
+        self.watch_files();
+    }
+
+    // Recarga todos los recursos desde un nuevo conjunto de paquetes. Si algo
+    // no carga se sigue con los recursos de antes.
+    fn load_assets(&mut self, assets: Assets) -> Result<(), GameError> {
+        // Un laberinto generado no viene de los paquetes
+        let map = match self.seed {
+            Some(seed) => generate::from_seed(seed),
+            None => Map::load(&assets, &self.map_file)?,
+        };
+        let (atmosphere, lighting) = load_level(&assets, &self.map_file, &map);
+        let mut renderer = Renderer::new(self.renderer.width, self.renderer.height, &assets, atmosphere, lighting)?;
+        let ui = UI::new(&assets)?;
+        let animated_sprite = AnimatedSprite::from_atlas(&assets, "sprite.atlas")?;
+        self.audio.reload(&assets)?;
+
+        renderer.threads = self.renderer.threads;
+        renderer.fov = self.renderer.fov;
+        renderer.camera = std::mem::take(&mut self.renderer.camera);
+        self.player = Player::new(&map);
+        self.enemies = Enemy::spawn_all(&map, &assets);
+        self.map = map;
+        self.renderer = renderer;
+        self.ui = ui;
+        self.animated_sprite = animated_sprite;
+        self.assets = assets;
+        self.watch_files();
+        Ok(())
+    }
+
+    fn show_victory_screen(&mut self, platform: &mut dyn Platform) {
+        self.present_screen(platform, |ui, buffer, width, height| ui.draw_victory_screen(buffer, width, height));
         if !self.victory_sound_played {
-            self.audio.play_victory();
+            self.audio.play_cue("sting.victory");
             self.victory_sound_played = true;
         }
-        if window.is_key_down(Key::Space) {
+        if platform.is_key_down(Key::Space) {
             self.reset_game();
-            self.audio.play_background_music("assets/nobodynocrimets.mp3"); 
         }
     }
     
-    fn show_game_over_screen(&mut self, window: &mut Window) {
-        self.ui.show_game_over_screen(window);
+    fn show_game_over_screen(&mut self, platform: &mut dyn Platform) {
+        self.present_screen(platform, |ui, buffer, width, height| ui.draw_game_over_screen(buffer, width, height));
         if !self.game_over_sound_played {
-            self.audio.play_game_over();
+            self.audio.play_cue("sting.gameover");
             self.game_over_sound_played = true;
         }
-        if window.is_key_down(Key::Space) {
+        if platform.is_key_down(Key::Space) {
             self.reset_game();
-            self.audio.play_background_music("assets/nobodynocrimets.mp3"); 
         }
     }
     
 
+    // Reaparece en el ultimo punto de control con los enemigos en su inicio
+    fn respawn(&mut self) {
+        self.player.respawn();
+        self.enemies = Enemy::spawn_all(&self.map, &self.assets);
+        self.tension = Tension::new();
+        self.look.reset();
+        println!("Lost a life, {} left", self.player.health.lives);
+    }
+
+    // Abre o cierra la consola con su sonido
+    fn toggle_console(&mut self) {
+        self.console.toggle();
+        self.audio.play_cue("ui.console");
+    }
+
     fn reset_game(&mut self) {
         self.player = Player::new(&self.map);
-        self.enemy = Enemy::new(&self.map);
+        self.enemies = Enemy::spawn_all(&self.map, &self.assets);
+        self.tension = Tension::new();
         self.current_state = State::Playing;
         self.victory_sound_played = false;
         self.game_over_sound_played = false;
-        self.audio.play_background_music("assets/nobodynocrimets.mp3"); 
+        self.audio.play_cue("music.chase");
     }
     
 }
 
-fn main() {
-    let frame_duration = Duration::from_secs_f64(1.0 / TARGET_FPS as f64);
+fn level_settings(assets: &Assets, map_file: &str, map: &Map) -> Result<(Atmosphere, Lighting), GameError> {
+    let config = Map::level_config(assets, map_file)?;
+    let invalid = |e| GameError::config(&Map::level_file(map_file), e);
+    Ok((Atmosphere::from_config(&config).map_err(invalid)?, Lighting::from_config(&config, map).map_err(invalid)?))
+}
+
+// Un .cfg de nivel invalido no impide jugar: se avisa y se usan los valores por defecto
+fn load_level(assets: &Assets, map_file: &str, map: &Map) -> (Atmosphere, Lighting) {
+    level_settings(assets, map_file, map).unwrap_or_else(|e| {
+        println!("Invalid level settings for '{}', using defaults: {}", map_file, e);
+        (Atmosphere::new(), Lighting::empty(map))
+    })
+}
+
+// Ajustes de video invalidos: se avisa y se usa la resolucion por defecto
+fn load_display(settings: &Config, window_size: (usize, usize)) -> Display {
+    Display::from_config(settings, window_size).unwrap_or_else(|e| {
+        println!("Invalid video settings, using defaults: {}", e);
+        Display::new(display::Resolution::Fixed(window_size.0, window_size.1))
+    })
+}
+
+// De lejos a cerca para que los mas cercanos tapen a los demas
+fn render_enemies(renderer: &mut Renderer, enemies: &[Enemy], player: &Player) {
+    let mut order: Vec<&Enemy> = enemies.iter().collect();
+    order.sort_by(|a, b| b.distance_to(player).total_cmp(&a.distance_to(player)));
+    for enemy in order {
+        if let Some(texture) = enemy.sprite_frame(player) {
+            renderer.render_sprite(player, texture, enemy.x, enemy.y);
+        }
+    }
+}
+
+// Hilos de render de `[video] threads` en settings.cfg; 0 o sin valor = uno por nucleo
+fn render_threads(settings: &Config) -> usize {
+    let threads = settings.get_f64("video", "threads").unwrap_or_else(|e| {
+        println!("{}", e);
+        None
+    });
+    match threads {
+        Some(n) if n >= 1.0 => n as usize,
+        _ => std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+    }
+}
+
+fn load_settings() -> Config {
+    // Sin archivo de ajustes se usan los valores por defecto
+    if !std::path::Path::new(SETTINGS_FILE).exists() {
+        return Config::new();
+    }
+    Config::load(SETTINGS_FILE).unwrap_or_else(|e| {
+        println!("{}", e);
+        Config::new()
+    })
+}
+
+// `play` y `replay`: abre la ventana y corre el juego
+fn run_game(options: PlayOptions, replay: Option<Recording>) -> Result<(), GameError> {
+    let assets = Assets::with_packs(BASE_DIR, PACKS_DIR, &options.packs)?;
+    let settings = load_settings();
+    let threads = options.threads.unwrap_or_else(|| render_threads(&settings));
+    let window_size = options.size.unwrap_or((WIDTH, HEIGHT));
+
+    let seed = replay.as_ref().map_or(options.seed, |recording| recording.seed);
+    let map_file = match (&replay, seed) {
+        (Some(recording), _) => recording.map.clone(),
+        (None, Some(seed)) => format!("seed-{}.txt", seed),
+        (None, None) => options.map.clone().unwrap_or_else(|| MAP_FILE.to_string()),
+    };
+    // Un mapa generado no viene de assets
+    let map = match seed {
+        Some(seed) => generate::from_seed(seed),
+        None => Map::load(&assets, &map_file)?,
+    };
+
+    if let Some(frames) = options.bench {
+        let (atmosphere, lighting) = load_level(&assets, &map_file, &map);
+        let display = load_display(&settings, window_size);
+        let (width, height) = display.render_size(window_size);
+        let renderer = Renderer::new(width, height, &assets, atmosphere, lighting)?;
+        return Ok(bench::run(&map, renderer, threads, frames)?);
+    }
+
+    let mut audio_backend = if options.no_audio || options.headless {
+        Box::new(NullBackend::new())
+    } else {
+        audio_backend::default_backend()
+    };
+    let mut cue_log: Option<CueLog> = None;
+    if options.audio_log.is_some() {
+        let (recording, log) = RecordingBackend::new(audio_backend);
+        audio_backend = Box::new(recording);
+        cue_log = Some(log);
+    }
+
+    let fps = options.fps.unwrap_or(TARGET_FPS);
+    let frame_duration = Duration::from_secs_f64(1.0 / fps as f64);
 
-    let mut window = Window::new(
-        "No body, no crime",
-        WIDTH,
-        HEIGHT,
-        WindowOptions::default(),
-    ).unwrap_or_else(|e| { panic!("{}", e); });
+    let mut platform: Box<dyn Platform> = if options.headless {
+        let mut headless = Headless::new(window_size.0, window_size.1);
+        if let Some(file) = &options.script {
+            headless = headless.with_script(Script::load(file)?);
+        }
+        if let Some(dir) = &options.capture {
+            headless = headless.capture_to(dir)?;
+        }
+        Box::new(headless)
+    } else {
+        Box::new(MinifbPlatform::new("No body, no crime", window_size.0, window_size.1)?)
+    };
+
+    let mut game_state = match GameState::new(assets, settings, audio_backend, map, &map_file, window_size) {
+        Ok(game_state) => game_state,
+        Err(e) => {
+            // Sin ventana no hay quien cierre la pantalla de error
+            if !options.headless {
+                show_error_screen(platform.as_mut(), &e);
+            }
+            return Err(e);
+        }
+    };
+    game_state.renderer.threads = threads;
+    if options.headless {
+        game_state.fixed_dt = Some(1.0 / fps as f64);
+    }
+    game_state.seed = seed;
 
-    window.set_cursor_visibility(false);
+    let scripts = std::path::Path::new(commands::STARTUP_SCRIPT).exists().then_some(commands::STARTUP_SCRIPT);
+    for script in scripts.into_iter().chain(options.exec.as_deref()) {
+        if let Err(e) = game_state.run_script(script) {
+            println!("{}", e);
+        }
+    }
 
-    let mut game_state = GameState::new();
+    let replaying = replay.is_some();
+    if let Some(filename) = options.record {
+        game_state.recording = Some((Recording::new(&game_state.map_file, seed), filename));
+    }
+    if let Some(recording) = replay {
+        game_state.playback = Some(Playback::new(recording));
+    }
+    if options.skip_intro || replaying {
+        game_state.start_game();
+    }
 
-    while window.is_open() && !window.is_key_down(Key::Escape) {
+    let mut frames = 0;
+    while platform.is_open() {
+        // Escape cierra la consola si esta abierta; si no, sale del juego
+        if platform.is_key_pressed(Key::Escape, KeyRepeat::No) {
+            if !game_state.console.open {
+                break;
+            }
+            game_state.toggle_console();
+        }
         let frame_start = Instant::now();
 
+        game_state.update_audio();
+        game_state.hot_reload();
+        game_state.sync_resolution(platform.as_ref());
+        let platform = platform.as_mut();
         match game_state.current_state {
-            State::Welcome => game_state.show_welcome_screen(&mut window),
-            State::Playing => game_state.play(&mut window),
-            State::Victory => game_state.show_victory_screen(&mut window),
-            State::GameOver => game_state.show_game_over_screen(&mut window),
+            State::Welcome => game_state.show_welcome_screen(platform),
+            State::Options => game_state.show_options_screen(platform),
+            State::Playing => game_state.play(platform),
+            State::Editor => game_state.show_editor(platform),
+            State::Victory => game_state.show_victory_screen(platform),
+            State::GameOver => game_state.show_game_over_screen(platform),
+        }
+        game_state.update_cursor(platform);
+        frames += 1;
+        if let Some(playback) = &game_state.playback {
+            if playback.finished() || !matches!(game_state.current_state, State::Playing) {
+                println!("Replay finished after {} frames: {}", playback.frames_played(), game_state.outcome());
+                break;
+            }
         }
 
+        // Sin ventana se corre tan rapido como se pueda
         let frame_end = Instant::now();
         let frame_time = frame_end.duration_since(frame_start);
-        if frame_time < frame_duration {
+        if frame_time < frame_duration && !options.headless {
             std::thread::sleep(frame_duration - frame_time);
         }
     }
+    if options.headless && !replaying {
+        println!("Headless session ended after {} frames: {}", frames, game_state.outcome());
+    }
+
+    game_state.finish_recording();
+    if let (Some(log), Some(filename)) = (cue_log, options.audio_log) {
+        log.save(&filename)?;
+    }
+    Ok(())
+}
+
+// Si el juego no llega a cargar, el error queda en la ventana hasta que se
+// cierre; el mismo mensaje se imprime al salir
+fn show_error_screen(platform: &mut dyn Platform, error: &GameError) {
+    let (width, height) = platform.size();
+    let mut buffer = vec![0; width * height];
+    let columns = (width.saturating_sub(40) / 8).max(1);
+    let message = error.to_string();
+    let mut lines: Vec<String> = message.chars()
+        .collect::<Vec<_>>()
+        .chunks(columns)
+        .map(|chunk| chunk.iter().collect())
+        .collect();
+    if let Some(file) = error.file() {
+        lines.push(String::new());
+        lines.push(format!("File: {}", file));
+    }
+    lines.push(String::new());
+    lines.push("Press Escape to quit".to_string());
+    UI::text_only().draw_text_screen(&mut buffer, width, "Error", &lines);
+
+    platform.set_cursor_grab(false);
+    while platform.is_open() && !platform.is_key_down(Key::Escape) {
+        platform.present(&buffer, width, height);
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+// `render`: dibuja un solo cuadro desde una pose y lo guarda como PNG
+fn render_frame(options: cli::RenderOptions) -> Result<(), GameError> {
+    let assets = Assets::with_packs(BASE_DIR, PACKS_DIR, &options.packs)?;
+    let map = Map::load(&assets, &options.map)?;
+    let (x, y, angle) = options.pose;
+    if !map.is_walkable(x, y) {
+        return Err(GameError::Other(format!("--pose {}, {} is not an open cell of '{}'", x, y, options.map)));
+    }
+
+    let (atmosphere, lighting) = level_settings(&assets, &options.map, &map)?;
+    let (width, height) = options.size.unwrap_or((WIDTH, HEIGHT));
+    let mut renderer = Renderer::new(width, height, &assets, atmosphere, lighting)?;
+    renderer.threads = render_threads(&load_settings());
+    let mut player = Player::new(&map);
+    (player.x, player.y, player.angle) = (x, y, angle);
+    let enemies = Enemy::spawn_all(&map, &assets);
+
+    renderer.render_3d(&map, &player);
+    render_enemies(&mut renderer, &enemies, &player);
+    println!("{}", commands::save_screenshot(&renderer, &options.out)?);
+    Ok(())
+}
+
+// `generate`: escribe un laberinto nuevo en el formato de los mapas
+fn generate_map(options: cli::GenerateOptions) -> Result<(), String> {
+    let (width, height) = options.size;
+    let map = generate::generate(options.algorithm, width, height, options.seed);
+    std::fs::write(&options.out, map.to_text())
+        .map_err(|e| format!("Failed to write '{}': {}", options.out, e))?;
+    println!("Wrote {} ({}x{} cells, seed {})", options.out, width, height, options.seed);
+    Ok(())
+}
+
+// `check-map`: las mismas validaciones que el editor antes de guardar
+fn check_map(filename: &str, packs: &[String]) -> Result<(), GameError> {
+    let assets = Assets::with_packs(BASE_DIR, PACKS_DIR, packs)?;
+    let problems = Map::parse(&assets.read_to_string(filename)?).check();
+    if !problems.is_empty() {
+        return Err(GameError::Other(format!("{}:\n  {}", filename, problems.join("\n  "))));
+    }
+    println!("{}: ok", filename);
+    Ok(())
+}
+
+fn main() {
+    let command = match cli::parse(std::env::args().skip(1).collect()) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {}\nRun 'proyecto1 help' for usage.", e);
+            std::process::exit(2);
+        }
+    };
+
+    let result = match command {
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+        Command::Play(options) => run_game(options, None),
+        Command::Replay(file, options) => Recording::load(&file)
+            .map_err(GameError::from)
+            .and_then(|recording| run_game(options, Some(recording))),
+        Command::Render(options) => render_frame(options),
+        Command::Generate(options) => generate_map(options).map_err(GameError::from),
+        Command::CheckMap(file, packs) => check_map(&file, &packs),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
 }
@@ -1,22 +1,49 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::assets::Assets;
+use crate::config::Config;
+use crate::error::GameError;
+use crate::pathfinding;
 
+// Celdas del mapa: '+', '-' y '|' son paredes, 'p' el inicio del jugador,
+// 'E' el de un enemigo, 'g' la meta, 'c' un punto de control y ' ' piso.
+#[derive(Clone)]
 pub struct Map {
     data: Vec<Vec<char>>,
 }
 
 impl Map {
-    pub fn load(filename: &str) -> Self {
-        match File::open(filename) {
-            Ok(file) => {
-                let reader = BufReader::new(file);
-                let data = reader.lines()
-                    .map(|line| line.unwrap().chars().collect())
-                    .collect();
-                Map { data }
-            },
-            Err(e) => panic!("Failed to open map file '{}': {}", filename, e),
+    // Rechaza mapas sin inicio del jugador o del enemigo
+    pub fn load(assets: &Assets, filename: &str) -> Result<Self, GameError> {
+        let map = Map::parse(&assets.read_to_string(filename)?);
+        for (cell, what) in [('p', "player start"), ('E', "enemy start")] {
+            if !map.data.iter().any(|row| row.contains(&cell)) {
+                return Err(GameError::Map { file: filename.to_string(), message: format!("no {} ('{}')", what, cell) });
+            }
+        }
+        Ok(map)
+    }
+
+    // Ajustes del nivel (ambiente, luces) en el archivo que acompana al mapa:
+    // maze.txt -> maze.cfg. Si no existe el nivel usa los valores por defecto.
+    pub fn level_config(assets: &Assets, filename: &str) -> Result<Config, GameError> {
+        let file = Map::level_file(filename);
+        if !assets.exists(&file) {
+            return Ok(Config::new());
         }
+        Config::parse(&assets.read_to_string(&file)?).map_err(|e| GameError::config(&file, e))
+    }
+
+    pub fn level_file(filename: &str) -> String {
+        match filename.rsplit_once('.') {
+            Some((stem, _)) => format!("{}.cfg", stem),
+            None => format!("{}.cfg", filename),
+        }
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let data = text.lines()
+            .map(|line| line.chars().collect())
+            .collect();
+        Map { data }
     }
 
     pub fn is_wall(&self, x: f64, y: f64) -> bool {
@@ -25,6 +52,13 @@ impl Map {
         self.get_cell(map_x, map_y) == '+' || self.get_cell(map_x, map_y) == '-' || self.get_cell(map_x, map_y) == '|'
     }
 
+    // Dentro del mapa y fuera de las paredes
+    pub fn is_walkable(&self, x: f64, y: f64) -> bool {
+        x >= 0.0 && y >= 0.0
+            && self.data.get(y as usize).is_some_and(|row| (x as usize) < row.len())
+            && !self.is_wall(x, y)
+    }
+
     pub fn get_cell(&self, x: usize, y: usize) -> char {
         self.data.get(y).and_then(|row| row.get(x)).cloned().unwrap_or(' ')
     }
@@ -41,15 +75,79 @@ impl Map {
     }
 
 
-    pub fn find_enemy_start(&self) -> (f64, f64) {
+    // Centro de cada celda 'E', por filas
+    pub fn enemy_starts(&self) -> Vec<(f64, f64)> {
+        self.find('E').into_iter().map(|(x, y)| (x as f64 + 0.5, y as f64 + 0.5)).collect()
+    }
+
+    // Celdas con el caracter `cell`, por filas
+    pub fn find(&self, cell: char) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
         for (y, row) in self.data.iter().enumerate() {
-            for (x, &cell) in row.iter().enumerate() {
-                if cell == 'E' {
-                    return (x as f64 + 0.5, y as f64 + 0.5);
+            for (x, &c) in row.iter().enumerate() {
+                if c == cell {
+                    cells.push((x, y));
                 }
             }
         }
-        panic!("Enemy start position not found in map");
+        cells
+    }
+
+    // Cambia una celda; las filas cortas se rellenan con piso
+    pub fn set_cell(&mut self, x: usize, y: usize, cell: char) {
+        let Some(row) = self.data.get_mut(y) else {
+            return;
+        };
+        if row.len() <= x {
+            row.resize(x + 1, ' ');
+        }
+        row[x] = cell;
+    }
+
+    // Problemas que impiden jugar el mapa (vacio si se puede jugar): inicios,
+    // meta, bordes abiertos y que la meta se pueda alcanzar
+    pub fn check(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let starts = self.find('p');
+        if starts.len() != 1 {
+            problems.push(format!("needs exactly one player start ('p'), found {}", starts.len()));
+        }
+        if self.find('E').is_empty() {
+            problems.push("no enemy start ('E')".to_string());
+        }
+        let goals = self.find('g');
+        if goals.is_empty() {
+            problems.push("no goal ('g')".to_string());
+        }
+
+        // Fuera del mapa no hay paredes: una celda de piso en el borde deja salir al jugador
+        let open_edge = (0..self.height()).flat_map(|y| (0..self.data[y].len()).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.is_walkable(x as f64 + 0.5, y as f64 + 0.5))
+            .find(|&(x, y)| {
+                x == 0 || y == 0 || y + 1 == self.height()
+                    || [(x + 1, y), (x, y - 1), (x, y + 1)].iter().any(|&(nx, ny)| nx >= self.data[ny].len())
+            });
+        if let Some((x, y)) = open_edge {
+            problems.push(format!("open edge at {}, {}: the border must be walls", x, y));
+        }
+
+        if let [start] = starts[..] {
+            let reachable = goals.iter().any(|&goal| pathfinding::find_path(self, start, goal).is_some());
+            if !goals.is_empty() && !reachable {
+                problems.push("the goal can't be reached from the player start".to_string());
+            }
+        }
+        problems
+    }
+
+    // El mapa en el formato de los archivos .txt
+    pub fn to_text(&self) -> String {
+        let mut text: String = self.data.iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        text.push('\n');
+        text
     }
 
     pub fn is_player_at_goal(&self, player: &crate::player::Player) -> bool {
@@ -58,8 +156,9 @@ impl Map {
         self.get_cell(map_x, map_y) == 'g'
     }
 
+    // La fila mas larga; las filas pueden tener distinto largo
     pub fn width(&self) -> usize {
-        self.data[0].len()
+        self.data.iter().map(Vec::len).max().unwrap_or(0)
     }
 
     pub fn height(&self) -> usize {
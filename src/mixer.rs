@@ -0,0 +1,128 @@
+use crate::config::Config;
+use crate::synth::Synth;
+use crate::tension::TensionLevel;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Bus {
+    Master,
+    Music,
+    Sfx,
+    Ui,
+}
+
+pub const BUSES: [Bus; 4] = [Bus::Master, Bus::Music, Bus::Sfx, Bus::Ui];
+
+impl Bus {
+    pub fn name(self) -> &'static str {
+        match self {
+            Bus::Master => "master",
+            Bus::Music => "music",
+            Bus::Sfx => "sfx",
+            Bus::Ui => "ui",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+// Un sonido con nombre que el juego puede disparar sin conocer el archivo
+pub struct Cue {
+    pub name: &'static str,
+    pub file: &'static str,
+    pub bus: Bus,
+    pub looping: bool,
+    // Baja la musica a este nivel mientras suena (1.0 = sin ducking)
+    pub duck_music: f32,
+    // Si el archivo puede faltar (p. ej. stems que solo trae un paquete)
+    pub optional: bool,
+    // Capa que acompana a otra pista de musica y entra a partir de un nivel de tension
+    pub stem: Option<(&'static str, TensionLevel)>,
+    // Sonido generado que suena si falta el archivo
+    pub synth: Option<Synth>,
+}
+
+pub const CUES: [Cue; 10] = [
+    Cue { name: "music.chase", file: "nobodynocrimets.mp3", bus: Bus::Music, looping: true, duck_music: 1.0, optional: false, stem: None, synth: None },
+    Cue { name: "music.stem.alert", file: "music_alert.mp3", bus: Bus::Music, looping: true, duck_music: 1.0, optional: true, stem: Some(("music.chase", TensionLevel::Alert)), synth: Some(Synth::Heartbeat { bpm: 70.0 }) },
+    Cue { name: "music.stem.chase", file: "music_chase.mp3", bus: Bus::Music, looping: true, duck_music: 1.0, optional: true, stem: Some(("music.chase", TensionLevel::Chase)), synth: Some(Synth::Heartbeat { bpm: 130.0 }) },
+    Cue { name: "sting.victory", file: "victory.mp3", bus: Bus::Music, looping: false, duck_music: 1.0, optional: false, stem: None, synth: None },
+    Cue { name: "sting.gameover", file: "gameover1.mp3", bus: Bus::Music, looping: false, duck_music: 1.0, optional: false, stem: None, synth: None },
+    Cue { name: "sfx.footsteps", file: "footsteps.mp3", bus: Bus::Sfx, looping: true, duck_music: 1.0, optional: false, stem: None, synth: None },
+    // Los assets base no traen un sonido para el enemigo: suena un gruñido generado hasta que un paquete agregue enemy.mp3
+    Cue { name: "sfx.enemy", file: "enemy.mp3", bus: Bus::Sfx, looping: false, duck_music: 0.4, optional: true, stem: None, synth: Some(Synth::Growl { seconds: 0.9 }) },
+    // Sonidos de menus y consola; sin archivo suenan pitidos cortos
+    Cue { name: "ui.move", file: "ui_move.mp3", bus: Bus::Ui, looping: false, duck_music: 1.0, optional: true, stem: None, synth: Some(Synth::Blip { hz: 660.0, seconds: 0.05 }) },
+    Cue { name: "ui.select", file: "ui_select.mp3", bus: Bus::Ui, looping: false, duck_music: 1.0, optional: true, stem: None, synth: Some(Synth::Blip { hz: 880.0, seconds: 0.1 }) },
+    Cue { name: "ui.console", file: "ui_console.mp3", bus: Bus::Ui, looping: false, duck_music: 1.0, optional: true, stem: None, synth: Some(Synth::Blip { hz: 440.0, seconds: 0.08 }) },
+];
+
+pub fn find_cue(name: &str) -> Option<&'static Cue> {
+    CUES.iter().find(|cue| cue.name == name)
+}
+
+// Volumen y silencio por bus; se guardan en la seccion [audio] de settings.cfg
+#[derive(Clone, Copy)]
+pub struct MixerSettings {
+    volume: [f32; 4],
+    muted: [bool; 4],
+}
+
+impl Default for MixerSettings {
+    fn default() -> Self {
+        MixerSettings::new()
+    }
+}
+
+impl MixerSettings {
+    pub fn new() -> Self {
+        MixerSettings { volume: [1.0, 0.5, 0.8, 0.8], muted: [false; 4] }
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let mut settings = MixerSettings::new();
+        for bus in BUSES {
+            if let Some(volume) = config.get_f64("audio", &format!("{}_volume", bus.name()))? {
+                settings.volume[bus.index()] = (volume as f32).clamp(0.0, 1.0);
+            }
+            if let Some(muted) = config.get_bool("audio", &format!("{}_muted", bus.name()))? {
+                settings.muted[bus.index()] = muted;
+            }
+        }
+        Ok(settings)
+    }
+
+    pub fn write_config(&self, config: &mut Config) {
+        for bus in BUSES {
+            config.set("audio", &format!("{}_volume", bus.name()), &format!("{:.2}", self.volume(bus)));
+            config.set("audio", &format!("{}_muted", bus.name()), &self.is_muted(bus).to_string());
+        }
+    }
+
+    pub fn volume(&self, bus: Bus) -> f32 {
+        self.volume[bus.index()]
+    }
+
+    pub fn set_volume(&mut self, bus: Bus, volume: f32) {
+        self.volume[bus.index()] = volume.clamp(0.0, 1.0);
+    }
+
+    pub fn is_muted(&self, bus: Bus) -> bool {
+        self.muted[bus.index()]
+    }
+
+    pub fn toggle_mute(&mut self, bus: Bus) {
+        self.muted[bus.index()] = !self.muted[bus.index()];
+    }
+
+    // Ganancia final de un bus, incluyendo el master
+    pub fn gain(&self, bus: Bus) -> f32 {
+        let level = |b: Bus| if self.is_muted(b) { 0.0 } else { self.volume(b) };
+        if bus == Bus::Master {
+            level(Bus::Master)
+        } else {
+            level(Bus::Master) * level(bus)
+        }
+    }
+}
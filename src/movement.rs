@@ -0,0 +1,127 @@
+// Aceleracion y frenado en celdas por segundo al cuadrado
+const ACCELERATION: f64 = 14.0;
+const FRICTION: f64 = 10.0;
+// Stamina (0..1) que se gasta y se recupera por segundo
+const STAMINA_DRAIN: f64 = 0.25;
+const STAMINA_REGEN: f64 = 0.15;
+// Segundos sin correr antes de empezar a recuperar stamina
+const REGEN_DELAY: f64 = 1.0;
+// Al agotarse hay que recuperar al menos esto para volver a correr
+const RECOVER_STAMINA: f64 = 0.3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MoveMode {
+    Walk,
+    Sprint,
+    Crouch,
+}
+
+impl MoveMode {
+    // Velocidad maxima en celdas por segundo
+    pub fn max_speed(self) -> f64 {
+        match self {
+            MoveMode::Walk => 2.0,
+            MoveMode::Sprint => 3.4,
+            MoveMode::Crouch => 1.0,
+        }
+    }
+
+    // Distancia en celdas a la que un enemigo oye los pasos a toda velocidad
+    pub fn noise(self) -> f64 {
+        match self {
+            MoveMode::Walk => 3.0,
+            MoveMode::Sprint => 7.0,
+            MoveMode::Crouch => 0.8,
+        }
+    }
+}
+
+// Como se mueve el jugador: velocidad con aceleracion y friccion, el modo
+// (caminar, correr, agacharse) y la stamina que gasta correr.
+pub struct Movement {
+    pub mode: MoveMode,
+    // Velocidad en celdas por segundo, en coordenadas del mapa
+    pub velocity: (f64, f64),
+    pub stamina: f64,
+    // Se quedo sin stamina y todavia no recupero `RECOVER_STAMINA`
+    pub exhausted: bool,
+    rest: f64,
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Movement::new()
+    }
+}
+
+impl Movement {
+    pub fn new() -> Self {
+        Movement { mode: MoveMode::Walk, velocity: (0.0, 0.0), stamina: 1.0, exhausted: false, rest: 0.0 }
+    }
+
+    // Avanza un cuadro hacia `wish` (direccion en el mapa, largo 0 o 1) y
+    // devuelve cuanto hay que desplazarse
+    pub fn update(&mut self, wish: (f64, f64), sprint: bool, crouch: bool, dt: f64) -> (f64, f64) {
+        let moving = wish != (0.0, 0.0);
+        self.mode = if crouch {
+            MoveMode::Crouch
+        } else if sprint && moving && !self.exhausted && self.stamina > 0.0 {
+            MoveMode::Sprint
+        } else {
+            MoveMode::Walk
+        };
+        self.update_stamina(dt);
+
+        // Acelera hacia la velocidad pedida; sin teclas solo frena
+        let max_speed = self.mode.max_speed();
+        let target = (wish.0 * max_speed, wish.1 * max_speed);
+        let rate = if moving { ACCELERATION } else { FRICTION };
+        let (dx, dy) = (target.0 - self.velocity.0, target.1 - self.velocity.1);
+        let distance = (dx * dx + dy * dy).sqrt();
+        let step = rate * dt;
+        self.velocity = if distance <= step {
+            target
+        } else {
+            (self.velocity.0 + dx / distance * step, self.velocity.1 + dy / distance * step)
+        };
+        (self.velocity.0 * dt, self.velocity.1 * dt)
+    }
+
+    fn update_stamina(&mut self, dt: f64) {
+        if self.mode == MoveMode::Sprint {
+            self.rest = 0.0;
+            self.stamina = (self.stamina - STAMINA_DRAIN * dt).max(0.0);
+            if self.stamina == 0.0 {
+                self.exhausted = true;
+            }
+            return;
+        }
+        self.rest += dt;
+        if self.rest >= REGEN_DELAY {
+            self.stamina = (self.stamina + STAMINA_REGEN * dt).min(1.0);
+        }
+        if self.stamina >= RECOVER_STAMINA {
+            self.exhausted = false;
+        }
+    }
+
+    pub fn speed(&self) -> f64 {
+        (self.velocity.0 * self.velocity.0 + self.velocity.1 * self.velocity.1).sqrt()
+    }
+
+    // Distancia a la que se oye al jugador: depende del modo y de que tan
+    // rapido va (quieto no hace ruido)
+    pub fn noise(&self) -> f64 {
+        let fraction = (self.speed() / self.mode.max_speed()).min(1.0);
+        self.mode.noise() * fraction
+    }
+
+    // Choco contra una pared en ese eje
+    pub fn stop_x(&mut self) {
+        self.velocity.0 = 0.0;
+    }
+
+    pub fn stop_y(&mut self) {
+        self.velocity.1 = 0.0;
+    }
+}
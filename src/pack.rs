@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use flate2::read::DeflateDecoder;
+
+use crate::config::Config;
+
+pub const MANIFEST_FILE: &str = "pack.cfg";
+
+// Un paquete de recursos: una carpeta o un .zip con la misma estructura que
+// `assets/` y un manifiesto opcional `pack.cfg`:
+//
+//   [pack]
+//   name = "Texturas oscuras"
+//   author = "..."
+//   priority = 10
+pub struct Pack {
+    pub id: String,
+    pub name: String,
+    pub author: String,
+    pub priority: i32,
+    source: Source,
+}
+
+enum Source {
+    Dir(PathBuf),
+    // El .zip se lee una sola vez al abrirlo; los paquetes son chicos
+    Zip { path: PathBuf, data: Vec<u8>, entries: HashMap<String, ZipEntry> },
+}
+
+#[derive(Clone, Copy)]
+struct ZipEntry {
+    method: u16,
+    compressed_size: usize,
+    uncompressed_size: usize,
+    local_header_offset: usize,
+}
+
+impl Pack {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let id = path.file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| format!("Invalid pack path '{}'", path.display()))?
+            .to_string();
+
+        let source = if path.is_dir() {
+            Source::Dir(path.to_path_buf())
+        } else if path.extension().and_then(|e| e.to_str()) == Some("zip") {
+            let data = fs::read(path)
+                .map_err(|e| format!("Failed to open pack '{}': {}", path.display(), e))?;
+            let entries = read_zip_directory(&data)
+                .map_err(|e| format!("Invalid zip pack '{}': {}", path.display(), e))?;
+            Source::Zip { path: path.to_path_buf(), data, entries }
+        } else {
+            return Err(format!("Pack '{}' is neither a folder nor a .zip", path.display()));
+        };
+
+        let mut pack = Pack { name: id.clone(), id, author: String::new(), priority: 0, source };
+
+        if let Some(bytes) = pack.read(MANIFEST_FILE)? {
+            let text = String::from_utf8_lossy(&bytes);
+            let manifest = Config::parse(&text)
+                .map_err(|e| format!("{} in pack '{}': {}", MANIFEST_FILE, pack.id, e))?;
+            if let Some(name) = manifest.get("pack", "name") {
+                pack.name = name.to_string();
+            }
+            if let Some(author) = manifest.get("pack", "author") {
+                pack.author = author.to_string();
+            }
+            if let Some(priority) = manifest.get_f64("pack", "priority")? {
+                pack.priority = priority as i32;
+            }
+        }
+
+        Ok(pack)
+    }
+
+    // Busca todos los paquetes (carpetas y .zip) dentro de `dir`
+    pub fn discover(dir: &str) -> Vec<Pack> {
+        let mut packs = Vec::new();
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return packs;
+        };
+        let mut paths: Vec<PathBuf> = read_dir.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        paths.sort();
+
+        for path in paths {
+            if !path.is_dir() && path.extension().and_then(|e| e.to_str()) != Some("zip") {
+                continue;
+            }
+            match Pack::open(&path) {
+                Ok(pack) => packs.push(pack),
+                Err(e) => println!("Skipping pack: {}", e),
+            }
+        }
+        packs
+    }
+
+    pub fn files(&self) -> Vec<String> {
+        match &self.source {
+            Source::Dir(root) => {
+                let mut files = Vec::new();
+                collect_files(root, root, &mut files);
+                files
+            }
+            Source::Zip { entries, .. } => entries.keys().filter(|k| *k != MANIFEST_FILE).cloned().collect(),
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        match &self.source {
+            Source::Dir(root) => root.join(name).is_file(),
+            Source::Zip { entries, .. } => entries.contains_key(name),
+        }
+    }
+
+    // Ruta en disco de un archivo del paquete; los .zip no tienen
+    pub fn path(&self, name: &str) -> Option<PathBuf> {
+        match &self.source {
+            Source::Dir(root) => Some(root.join(name)),
+            Source::Zip { .. } => None,
+        }
+    }
+
+    // Devuelve `Ok(None)` si el paquete no contiene el archivo
+    pub fn read(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+        match &self.source {
+            Source::Dir(root) => {
+                let path = root.join(name);
+                if !path.is_file() {
+                    return Ok(None);
+                }
+                fs::read(&path)
+                    .map(Some)
+                    .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))
+            }
+            Source::Zip { path, data, entries } => {
+                let Some(entry) = entries.get(name) else {
+                    return Ok(None);
+                };
+                read_zip_entry(data, entry)
+                    .map(Some)
+                    .map_err(|e| format!("{} in '{}': {}", name, path.display(), e))
+            }
+        }
+    }
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read_dir.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(root, &path, files);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let name = relative.to_string_lossy().replace('\\', "/");
+            if name != MANIFEST_FILE {
+                files.push(name);
+            }
+        }
+    }
+}
+
+// Lector minimo de zip: solo el directorio central y entradas "stored" o "deflate"
+fn read_zip_directory(data: &[u8]) -> Result<HashMap<String, ZipEntry>, String> {
+    const EOCD_SIGNATURE: u32 = 0x06054b50;
+    const CENTRAL_SIGNATURE: u32 = 0x02014b50;
+
+    let eocd = (0..data.len().saturating_sub(21))
+        .rev()
+        .find(|&i| read_u32(data, i) == Some(EOCD_SIGNATURE))
+        .ok_or("end of central directory not found")?;
+
+    let count = read_u16(data, eocd + 10).ok_or("truncated archive")? as usize;
+    let mut offset = read_u32(data, eocd + 16).ok_or("truncated archive")? as usize;
+    let mut entries = HashMap::new();
+
+    for _ in 0..count {
+        if read_u32(data, offset) != Some(CENTRAL_SIGNATURE) {
+            return Err("corrupt central directory".to_string());
+        }
+        let field = |at: usize| read_u16(data, offset + at).ok_or("truncated archive");
+        let method = field(10)?;
+        let name_len = field(28)? as usize;
+        let extra_len = field(30)? as usize;
+        let comment_len = field(32)? as usize;
+        let compressed_size = read_u32(data, offset + 20).ok_or("truncated archive")? as usize;
+        let uncompressed_size = read_u32(data, offset + 24).ok_or("truncated archive")? as usize;
+        let local_header_offset = read_u32(data, offset + 42).ok_or("truncated archive")? as usize;
+        let name = data.get(offset + 46..offset + 46 + name_len).ok_or("truncated archive")?;
+        let name = String::from_utf8_lossy(name).to_string();
+
+        if !name.ends_with('/') {
+            entries.insert(name, ZipEntry { method, compressed_size, uncompressed_size, local_header_offset });
+        }
+        offset += 46 + name_len + extra_len + comment_len;
+    }
+
+    Ok(entries)
+}
+
+fn read_zip_entry(data: &[u8], entry: &ZipEntry) -> Result<Vec<u8>, String> {
+    let header = entry.local_header_offset;
+    let name_len = read_u16(data, header + 26).ok_or("truncated archive")? as usize;
+    let extra_len = read_u16(data, header + 28).ok_or("truncated archive")? as usize;
+    let start = header + 30 + name_len + extra_len;
+    let compressed = data.get(start..start + entry.compressed_size).ok_or("truncated archive")?;
+
+    match entry.method {
+        0 => Ok(compressed.to_vec()),
+        8 => {
+            let mut out = Vec::with_capacity(entry.uncompressed_size);
+            DeflateDecoder::new(compressed)
+                .read_to_end(&mut out)
+                .map_err(|e| e.to_string())?;
+            Ok(out)
+        }
+        method => Err(format!("unsupported compression method {}", method)),
+    }
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    data.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::Assets;
+
+    // Carpeta temporal propia de cada prueba
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("proyecto1-pack-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // Zip con entradas "stored" (sin comprimir)
+    fn stored_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut central = Vec::new();
+        for (name, contents) in files {
+            let offset = data.len() as u32;
+            let sizes = [(contents.len() as u32).to_le_bytes(), (contents.len() as u32).to_le_bytes()].concat();
+            data.extend(0x04034b50u32.to_le_bytes());
+            data.extend([20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            data.extend(&sizes);
+            data.extend((name.len() as u16).to_le_bytes());
+            data.extend([0, 0]);
+            data.extend(name.as_bytes());
+            data.extend(*contents);
+
+            central.extend(0x02014b50u32.to_le_bytes());
+            central.extend([20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            central.extend(&sizes);
+            central.extend((name.len() as u16).to_le_bytes());
+            central.extend([0; 12]);
+            central.extend(offset.to_le_bytes());
+            central.extend(name.as_bytes());
+        }
+        let central_offset = data.len() as u32;
+        let count = (files.len() as u16).to_le_bytes();
+        data.extend(&central);
+        data.extend(0x06054b50u32.to_le_bytes());
+        data.extend([0, 0, 0, 0]);
+        data.extend(count);
+        data.extend(count);
+        data.extend((central.len() as u32).to_le_bytes());
+        data.extend(central_offset.to_le_bytes());
+        data.extend([0, 0]);
+        data
+    }
+
+    #[test]
+    fn reads_stored_zip_entries_and_manifest() {
+        let dir = temp_dir("stored");
+        let path = dir.join("dark.zip");
+        let manifest: &[u8] = b"[pack]\nname = \"Dark\"\npriority = 5\n";
+        fs::write(&path, stored_zip(&[("pack.cfg", manifest), ("maze.txt", b"+--+\n|pg|\n+--+\n")])).unwrap();
+
+        let pack = Pack::open(&path).unwrap();
+        assert_eq!(pack.name, "Dark");
+        assert_eq!(pack.priority, 5);
+        assert_eq!(pack.files(), ["maze.txt"]);
+        assert_eq!(pack.read("maze.txt").unwrap().unwrap(), b"+--+\n|pg|\n+--+\n");
+        assert!(pack.read("missing.png").unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_a_corrupt_zip() {
+        let dir = temp_dir("corrupt");
+        let path = dir.join("broken.zip");
+        let mut data = stored_zip(&[("maze.txt", b"+--+")]);
+        // El directorio central apunta a basura
+        let len = data.len();
+        data[len - 6..len - 2].copy_from_slice(&3u32.to_le_bytes());
+        fs::write(&path, data).unwrap();
+
+        let error = Pack::open(&path).err().unwrap();
+        assert!(error.contains("Invalid zip pack"), "{}", error);
+        fs::write(&path, b"not a zip").unwrap();
+        assert!(Pack::open(&path).is_err());
+    }
+
+    #[test]
+    fn reports_packs_that_replace_the_same_file() {
+        let dir = temp_dir("conflict");
+        fs::create_dir_all(dir.join("first")).unwrap();
+        fs::write(dir.join("first/welcome.png"), b"first").unwrap();
+        fs::write(dir.join("second.zip"), stored_zip(&[("welcome.png", b"second"), ("victory.png", b"only here")])).unwrap();
+
+        let ids = ["first".to_string(), "second".to_string()];
+        let assets = Assets::with_packs("assets", dir.to_str().unwrap(), &ids).unwrap();
+        let conflicts = assets.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].file, "welcome.png");
+        assert_eq!(conflicts[0].winner, "first");
+        assert_eq!(conflicts[0].overridden, ["second"]);
+        assert_eq!(assets.read("welcome.png").unwrap(), b"first");
+    }
+}
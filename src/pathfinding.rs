@@ -0,0 +1,61 @@
+use std::collections::VecDeque;
+use crate::map::Map;
+
+// Camino mas corto (BFS en 4 direcciones) entre dos celdas del mapa.
+// Devuelve las celdas a recorrer sin incluir `start`; `None` si no hay camino.
+pub fn find_path(map: &Map, start: (usize, usize), goal: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+    let (width, height) = (map.width(), map.height());
+    if start.0 >= width || start.1 >= height || goal.0 >= width || goal.1 >= height {
+        return None;
+    }
+
+    let mut came_from: Vec<Option<(usize, usize)>> = vec![None; width * height];
+    let mut queue = VecDeque::new();
+    came_from[start.1 * width + start.0] = Some(start);
+    queue.push_back(start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        if (x, y) == goal {
+            let mut path = Vec::new();
+            let mut cell = goal;
+            while cell != start {
+                path.push(cell);
+                cell = came_from[cell.1 * width + cell.0].unwrap();
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let neighbors = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbors {
+            if nx >= width || ny >= height || came_from[ny * width + nx].is_some() {
+                continue;
+            }
+            if map.is_wall(nx as f64 + 0.5, ny as f64 + 0.5) {
+                continue;
+            }
+            came_from[ny * width + nx] = Some((x, y));
+            queue.push_back((nx, ny));
+        }
+    }
+
+    None
+}
+
+// Recorre la linea entre dos puntos buscando paredes
+pub fn has_line_of_sight(map: &Map, from: (f64, f64), to: (f64, f64)) -> bool {
+    let dx = to.0 - from.0;
+    let dy = to.1 - from.1;
+    let distance = (dx * dx + dy * dy).sqrt();
+    let steps = (distance / 0.05).ceil() as usize;
+
+    (1..steps).all(|i| {
+        let t = i as f64 / steps as f64;
+        !map.is_wall(from.0 + dx * t, from.1 + dy * t)
+    })
+}
@@ -0,0 +1,344 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::RgbImage;
+
+// Lo que el juego necesita de una ventana: mostrar el framebuffer, el estado
+// del teclado y el mouse, y el tamano. El juego usa minifb; `Headless` corre
+// sin ventana con entrada escrita de antemano.
+pub trait Platform {
+    fn is_open(&self) -> bool;
+    fn size(&self) -> (usize, usize);
+    // Muestra `buffer` (del tamano de la ventana) y lee la entrada del cuadro siguiente
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize);
+    // Lee la entrada sin dibujar (p. ej. con la ventana minimizada)
+    fn update(&mut self);
+    fn is_key_down(&self, key: Key) -> bool;
+    // Si la tecla se presiono en este cuadro; con `KeyRepeat::Yes` tambien
+    // cuenta la repeticion de una tecla mantenida
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool;
+    // Posicion en la ventana; None si el mouse esta fuera
+    fn mouse_pos(&self) -> Option<(f32, f32)>;
+    // Cuanto se movio el mouse desde el cuadro anterior
+    fn mouse_delta(&self) -> (f32, f32);
+    fn is_mouse_down(&self, button: MouseButton) -> bool;
+    // Oculta el cursor mientras se juega y lo retiene en la ventana; un
+    // backend que no puede retenerlo lo deja a la vista (ver `MinifbPlatform`)
+    fn set_cursor_grab(&mut self, grab: bool);
+    // Texto escrito en este cuadro (para la consola)
+    fn take_text(&mut self) -> String;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Key {
+    W, A, S, D, E, F, M, O, P, Q, Y, Z,
+    Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+    Up, Down, Left, Right,
+    Space, Enter, Backspace, Tab, Escape, Backquote, F3,
+    LeftCtrl, RightCtrl, LeftShift, RightShift,
+}
+
+const KEY_NAMES: [(Key, &str); 38] = [
+    (Key::W, "W"), (Key::A, "A"), (Key::S, "S"), (Key::D, "D"), (Key::E, "E"), (Key::F, "F"),
+    (Key::M, "M"), (Key::O, "O"), (Key::P, "P"), (Key::Q, "Q"), (Key::Y, "Y"), (Key::Z, "Z"),
+    (Key::Key1, "1"), (Key::Key2, "2"), (Key::Key3, "3"), (Key::Key4, "4"), (Key::Key5, "5"),
+    (Key::Key6, "6"), (Key::Key7, "7"), (Key::Key8, "8"), (Key::Key9, "9"),
+    (Key::Up, "Up"), (Key::Down, "Down"), (Key::Left, "Left"), (Key::Right, "Right"),
+    (Key::Space, "Space"), (Key::Enter, "Enter"), (Key::Backspace, "Backspace"), (Key::Tab, "Tab"),
+    (Key::Escape, "Escape"), (Key::Backquote, "Backquote"), (Key::F3, "F3"),
+    (Key::LeftCtrl, "LeftCtrl"), (Key::RightCtrl, "RightCtrl"),
+    (Key::LeftShift, "LeftShift"), (Key::RightShift, "RightShift"),
+    // Nombres alternativos
+    (Key::LeftCtrl, "Ctrl"), (Key::LeftShift, "Shift"),
+];
+
+impl Key {
+    // Sin distinguir mayusculas: "w", "Space", "ctrl"
+    pub fn from_name(name: &str) -> Option<Key> {
+        KEY_NAMES.iter().find(|(_, n)| n.eq_ignore_ascii_case(name)).map(|&(key, _)| key)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum KeyRepeat {
+    Yes,
+    No,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MouseButton {
+    Left,
+    Right,
+}
+
+// Entrada sintetica para `Headless`, por numero de cuadro. El cuadro N es
+// el estado despues de mostrar N cuadros (las pantallas de menu leen las
+// teclas despues de mostrarse, asi que lo del cuadro 0 solo lo ve el juego).
+//
+//   # cuadro accion
+//   1 press Space          # presiona y suelta
+//   5 down W               # mantiene hasta el 'up'
+//   40 up W
+//   45 mouse 30 0          # mueve el mouse (dx dy)
+//   50 button left down
+//   60 text noclip         # texto para la consola
+//   90 close               # sin 'close' la sesion termina despues del ultimo evento
+pub struct Script {
+    events: Vec<(usize, Event)>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Event {
+    Down(Key),
+    Up(Key),
+    Press(Key),
+    Mouse(f32, f32),
+    Button(MouseButton, bool),
+    Text(String),
+    Close,
+}
+
+impl Script {
+    pub fn load(filename: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(filename)
+            .map_err(|e| format!("Failed to read script '{}': {}", filename, e))?;
+        Script::parse(&text).map_err(|e| format!("{}: {}", filename, e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut events = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let error = |message: &str| format!("line {}: {}", number + 1, message);
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut fields = line.splitn(3, ' ');
+            let frame = fields.next().unwrap_or("").parse::<usize>().map_err(|_| error("expected a frame number"))?;
+            let action = fields.next().unwrap_or("");
+            let rest = fields.next().unwrap_or("").trim();
+            let key = || Key::from_name(rest).ok_or_else(|| error(&format!("unknown key '{}'", rest)));
+            let event = match action {
+                "down" => Event::Down(key()?),
+                "up" => Event::Up(key()?),
+                "press" => Event::Press(key()?),
+                "mouse" => {
+                    let delta: Vec<f32> = rest.split_whitespace()
+                        .map(|v| v.parse::<f32>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| error("expected 'mouse DX DY'"))?;
+                    let [dx, dy] = delta[..] else {
+                        return Err(error("expected 'mouse DX DY'"));
+                    };
+                    Event::Mouse(dx, dy)
+                }
+                "button" => {
+                    let button = match rest.split_whitespace().next() {
+                        Some("left") => MouseButton::Left,
+                        Some("right") => MouseButton::Right,
+                        _ => return Err(error("expected 'button left|right down|up'")),
+                    };
+                    match rest.split_whitespace().nth(1) {
+                        Some("down") => Event::Button(button, true),
+                        Some("up") => Event::Button(button, false),
+                        _ => return Err(error("expected 'button left|right down|up'")),
+                    }
+                }
+                "text" => Event::Text(rest.to_string()),
+                "close" => Event::Close,
+                _ => return Err(error(&format!("unknown action '{}'", action))),
+            };
+            events.push((frame, event));
+        }
+        // Los eventos del mismo cuadro se aplican en el orden del archivo
+        events.sort_by_key(|&(frame, _)| frame);
+        Ok(Script { events })
+    }
+
+    // Ultimo cuadro con algun evento
+    fn last_frame(&self) -> Option<usize> {
+        self.events.last().map(|&(frame, _)| frame)
+    }
+}
+
+// Plataforma sin ventana: la entrada sale de un `Script` y cada cuadro
+// mostrado queda guardado (y opcionalmente se escribe como PNG).
+pub struct Headless {
+    size: (usize, usize),
+    frame: usize,
+    script: Script,
+    // Sin script la sesion sigue hasta que el juego la termine
+    end: Option<usize>,
+    closed: bool,
+    down: HashSet<Key>,
+    pressed: HashSet<Key>,
+    released_next: Vec<Key>,
+    buttons: HashSet<MouseButton>,
+    mouse: (f32, f32),
+    delta: (f32, f32),
+    text: String,
+    grabbed: bool,
+    last_frame: Vec<u32>,
+    capture: Option<PathBuf>,
+}
+
+impl Headless {
+    pub fn new(width: usize, height: usize) -> Self {
+        Headless {
+            size: (width, height),
+            frame: 0,
+            script: Script { events: Vec::new() },
+            end: None,
+            closed: false,
+            down: HashSet::new(),
+            pressed: HashSet::new(),
+            released_next: Vec::new(),
+            buttons: HashSet::new(),
+            mouse: (width as f32 / 2.0, height as f32 / 2.0),
+            delta: (0.0, 0.0),
+            text: String::new(),
+            grabbed: false,
+            last_frame: Vec::new(),
+            capture: None,
+        }
+    }
+
+    pub fn with_script(mut self, script: Script) -> Self {
+        self.end = script.last_frame();
+        self.script = script;
+        self.apply_events();
+        self
+    }
+
+    // Escribe cada cuadro mostrado en `dir` como frame-00000.png, ...
+    pub fn capture_to(mut self, dir: &str) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir, e))?;
+        self.capture = Some(PathBuf::from(dir));
+        Ok(self)
+    }
+
+    // Cuadros mostrados hasta ahora
+    pub fn frames(&self) -> usize {
+        self.frame
+    }
+
+    // El ultimo cuadro mostrado, al tamano de la ventana
+    pub fn last_frame(&self) -> &[u32] {
+        &self.last_frame
+    }
+
+    pub fn is_cursor_grabbed(&self) -> bool {
+        self.grabbed
+    }
+
+    // Pasa al cuadro siguiente: lo de un solo cuadro se descarta y se
+    // aplican los eventos del nuevo
+    fn advance(&mut self) {
+        self.frame += 1;
+        self.pressed.clear();
+        self.delta = (0.0, 0.0);
+        self.text.clear();
+        for key in std::mem::take(&mut self.released_next) {
+            self.down.remove(&key);
+        }
+        self.apply_events();
+    }
+
+    fn apply_events(&mut self) {
+        let start = self.script.events.partition_point(|&(frame, _)| frame < self.frame);
+        let end = self.script.events.partition_point(|&(frame, _)| frame <= self.frame);
+        let events: Vec<Event> = self.script.events[start..end].iter().map(|(_, event)| event.clone()).collect();
+        for event in events {
+            match event {
+                Event::Down(key) => {
+                    if self.down.insert(key) {
+                        self.pressed.insert(key);
+                    }
+                }
+                Event::Up(key) => {
+                    self.down.remove(&key);
+                }
+                Event::Press(key) => {
+                    self.down.insert(key);
+                    self.pressed.insert(key);
+                    self.released_next.push(key);
+                }
+                Event::Mouse(dx, dy) => {
+                    self.delta = (self.delta.0 + dx, self.delta.1 + dy);
+                    let (width, height) = self.size;
+                    self.mouse = ((self.mouse.0 + dx).clamp(0.0, width as f32), (self.mouse.1 + dy).clamp(0.0, height as f32));
+                }
+                Event::Button(button, true) => {
+                    self.buttons.insert(button);
+                }
+                Event::Button(button, false) => {
+                    self.buttons.remove(&button);
+                }
+                Event::Text(text) => self.text.push_str(&text),
+                Event::Close => self.closed = true,
+            }
+        }
+    }
+
+    fn save_capture(&self, dir: &Path, width: usize, height: usize) -> Result<(), String> {
+        let mut image = RgbImage::new(width as u32, height as u32);
+        for (pixel, &color) in image.pixels_mut().zip(&self.last_frame) {
+            pixel.0 = [(color >> 16) as u8, (color >> 8) as u8, color as u8];
+        }
+        let path = dir.join(format!("frame-{:05}.png", self.frame));
+        image.save(&path).map_err(|e| format!("Failed to save '{}': {}", path.display(), e))
+    }
+}
+
+impl Platform for Headless {
+    fn is_open(&self) -> bool {
+        !self.closed && self.end.is_none_or(|end| self.frame <= end)
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) {
+        self.last_frame = buffer.to_vec();
+        if let Some(dir) = &self.capture {
+            if let Err(e) = self.save_capture(dir, width, height) {
+                println!("{}", e);
+            }
+        }
+        self.advance();
+    }
+
+    fn update(&mut self) {
+        self.advance();
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.down.contains(&key)
+    }
+
+    fn is_key_pressed(&self, key: Key, _repeat: KeyRepeat) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    fn mouse_pos(&self) -> Option<(f32, f32)> {
+        Some(self.mouse)
+    }
+
+    fn mouse_delta(&self) -> (f32, f32) {
+        self.delta
+    }
+
+    fn is_mouse_down(&self, button: MouseButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    fn set_cursor_grab(&mut self, grab: bool) {
+        self.grabbed = grab;
+    }
+
+    fn take_text(&mut self) -> String {
+        std::mem::take(&mut self.text)
+    }
+}
@@ -1,22 +1,43 @@
 use crate::map::Map;
 use std::f64::consts::PI;
+use crate::health::Health;
+use crate::lighting::Flashlight;
+use crate::movement::Movement;
+
+// Hasta donde se puede mirar arriba o abajo, en fraccion del alto de la pantalla
+pub const MAX_PITCH: f64 = 0.4;
 
 pub struct Player {
     pub x: f64,
     pub y: f64,
     pub angle: f64,
+    // Cuanto baja el horizonte al mirar hacia arriba (negativo = hacia abajo),
+    // en fraccion del alto de la pantalla
+    pub pitch: f64,
+    pub flashlight: Flashlight,
+    pub movement: Movement,
+    pub health: Health,
+    // Donde reaparece al perder una vida: el inicio o el ultimo punto de control
+    pub checkpoint: (f64, f64),
+    // Atraviesa paredes (comando `noclip` de la consola)
+    pub noclip: bool,
     rotation_speed: f64,
 }
 
 impl Player {
     pub fn new(map: &Map) -> Self {
         let (x, y) = map.find_player_start();
-        Player { x, y, angle: 0.0, rotation_speed: 0.0 }
+        Player::at(x, y)
+    }
+
+    // Jugador nuevo en (x, y), que tambien es su punto de reaparicion
+    pub fn at(x: f64, y: f64) -> Self {
+        Player { x, y, angle: 0.0, pitch: 0.0, flashlight: Flashlight::new(), movement: Movement::new(), health: Health::new(), checkpoint: (x, y), noclip: false, rotation_speed: 0.0 }
     }
 
     pub fn update(&mut self, dt: f64) {
         self.angle += self.rotation_speed * dt;
-        self.rotation_speed *= 0.9; 
+        self.rotation_speed *= 0.9;
 
         if self.angle < 0.0 {
             self.angle += 2.0 * PI;
@@ -25,42 +46,59 @@ impl Player {
         }
     }
 
-    pub fn move_forward(&mut self, map: &Map, speed: f64) {
-        let new_x = self.x + self.angle.cos() * speed;
-        let new_y = self.y + self.angle.sin() * speed;
-        if !map.is_wall(new_x, new_y) {
-            self.x = new_x;
-            self.y = new_y;
-        }
+    // Gira la vista sola, frenando de a poco (p. ej. al recibir un golpe)
+    pub fn spin(&mut self, speed: f64) {
+        self.rotation_speed = speed;
     }
 
-    pub fn move_backward(&mut self, map: &Map, speed: f64) {
-        let new_x = self.x - self.angle.cos() * speed;
-        let new_y = self.y - self.angle.sin() * speed;
-        if !map.is_wall(new_x, new_y) {
-            self.x = new_x;
-            self.y = new_y;
+    // Camina segun las teclas: `forward` y `strafe` son -1, 0 o 1. Choca con
+    // las paredes eje por eje para deslizarse a lo largo de ellas.
+    pub fn walk(&mut self, map: &Map, forward: f64, strafe: f64, sprint: bool, crouch: bool, dt: f64) {
+        let (sin, cos) = self.angle.sin_cos();
+        let (mut wish_x, mut wish_y) = (cos * forward - sin * strafe, sin * forward + cos * strafe);
+        let length = (wish_x * wish_x + wish_y * wish_y).sqrt();
+        if length > 0.0 {
+            (wish_x, wish_y) = (wish_x / length, wish_y / length);
+        }
+        let (dx, dy) = self.movement.update((wish_x, wish_y), sprint, crouch, dt);
+        if self.noclip || !map.is_wall(self.x + dx, self.y) {
+            self.x += dx;
+        } else {
+            self.movement.stop_x();
+        }
+        if self.noclip || !map.is_wall(self.x, self.y + dy) {
+            self.y += dy;
+        } else {
+            self.movement.stop_y();
         }
     }
 
-    pub fn strafe_left(&mut self, map: &Map, speed: f64) {
-        let strafe_angle = self.angle - std::f64::consts::PI / 2.0;
-        let new_x = self.x + strafe_angle.cos() * speed;
-        let new_y = self.y + strafe_angle.sin() * speed;
-        if !map.is_wall(new_x, new_y) {
-            self.x = new_x;
-            self.y = new_y;
-        }
+    // Distancia en celdas a la que los enemigos oyen al jugador
+    pub fn noise(&self) -> f64 {
+        self.movement.noise()
     }
 
-    pub fn strafe_right(&mut self, map: &Map, speed: f64) {
-        let strafe_angle = self.angle + std::f64::consts::PI / 2.0;
-        let new_x = self.x + strafe_angle.cos() * speed;
-        let new_y = self.y + strafe_angle.sin() * speed;
-        if !map.is_wall(new_x, new_y) {
-            self.x = new_x;
-            self.y = new_y;
+    // Guarda el punto de control ('c') que esta pisando; devuelve true si es nuevo
+    pub fn touch_checkpoint(&mut self, map: &Map) -> bool {
+        let (cell_x, cell_y) = (self.x as usize, self.y as usize);
+        let center = (cell_x as f64 + 0.5, cell_y as f64 + 0.5);
+        if map.get_cell(cell_x, cell_y) != 'c' || self.checkpoint == center {
+            return false;
         }
+        self.checkpoint = center;
+        true
+    }
+
+    // Vuelve al ultimo punto de control sin velocidad ni inclinacion; la
+    // salud y las vidas las maneja `Health::lose_life`
+    pub fn respawn(&mut self) {
+        (self.x, self.y) = self.checkpoint;
+        self.pitch = 0.0;
+        self.movement = Movement::new();
+    }
+
+    pub fn look_up(&mut self, amount: f64) {
+        self.pitch = (self.pitch + amount).clamp(-MAX_PITCH, MAX_PITCH);
     }
 
     pub fn rotate(&mut self, angle: f64) {
@@ -1,31 +1,110 @@
-use image::GenericImageView;
+use image::{GenericImageView, Rgba, RgbaImage};
 use crate::map::Map;
 use crate::player::Player;
 use std::f64::consts::PI;
 use crate::enemy::Enemy;
+use crate::assets::Assets;
+use crate::atmosphere::Atmosphere;
+use crate::lighting::Lighting;
+use crate::camera::Camera;
+use crate::composite;
+use crate::error::GameError;
+use std::time::{Duration, Instant};
+
+// Cuanto tomo cada etapa del ultimo cuadro
+#[derive(Clone, Copy, Default, Debug)]
+pub struct FrameTimes {
+    pub raycast: Duration,
+    pub floor: Duration,
+    pub sprites: Duration,
+    pub ui: Duration,
+}
+
+// Avance de cada paso de `cast_ray`, en celdas
+pub const RAY_STEP: f64 = 0.01;
+
+pub const WALL_TEXTURE: &str = "walltexture1.jpg";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Pass {
+    // Rayos, cielo y paredes
+    Walls,
+    Floor,
+}
 
 pub struct Renderer {
     pub width: usize,
     pub height: usize,
+    pub atmosphere: Atmosphere,
+    pub lighting: Lighting,
+    // Hilos para `render_3d`; 1 = todo en el hilo principal
+    pub threads: usize,
+    // Campo de vision horizontal, en radianes
+    pub fov: f64,
+    // Balanceo y temblor que se suman a la vista del jugador
+    pub camera: Camera,
+    // Cuadro actual (por filas) y distancia a la pared de cada columna; se
+    // reutilizan entre cuadros y los sprites y la UI dibujan encima
+    pub frame: Vec<u32>,
+    pub depth: Vec<f64>,
+    pub times: FrameTimes,
+    // Cuadro por columnas donde escriben los hilos, y donde termina la pared
+    // de cada columna (para la pasada del piso)
+    columns: Vec<u32>,
+    wall_bottoms: Vec<usize>,
     texture: Vec<u32>,
     img_width: usize,
     img_height: usize,
 }
 
 impl Renderer {
-    pub fn new(width: usize, height: usize) -> Self {
-        let (texture, img_width, img_height) = Renderer::load_texture("assets/walltexture1.jpg");
-        Renderer { width, height, texture, img_width, img_height }
+    pub fn new(width: usize, height: usize, assets: &Assets, atmosphere: Atmosphere, lighting: Lighting) -> Result<Self, GameError> {
+        let (texture, img_width, img_height) = Renderer::load_texture(assets)?;
+        let mut renderer = Renderer {
+            width: 0,
+            height: 0,
+            atmosphere,
+            lighting,
+            threads: 1,
+            fov: PI / 3.0,
+            camera: Camera::new(),
+            frame: Vec::new(),
+            depth: Vec::new(),
+            times: FrameTimes::default(),
+            columns: Vec::new(),
+            wall_bottoms: Vec::new(),
+            texture,
+            img_width,
+            img_height,
+        };
+        renderer.resize(width, height);
+        Ok(renderer)
     }
 
-    fn load_texture(filename: &str) -> (Vec<u32>, usize, usize) {
-        let img = image::open(filename).expect("Failed to load texture");
+    // La resolucion de render cambio (p. ej. la ventana cambio de tamano)
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.frame = vec![0; width * height];
+        self.depth = vec![f64::MAX; width];
+        self.columns = vec![0; width * height];
+        self.wall_bottoms = vec![0; width];
+    }
+
+    // Vuelve a leer la textura de las paredes; si falla se queda la anterior
+    pub fn reload_texture(&mut self, assets: &Assets) -> Result<(), GameError> {
+        (self.texture, self.img_width, self.img_height) = Renderer::load_texture(assets)?;
+        Ok(())
+    }
+
+    fn load_texture(assets: &Assets) -> Result<(Vec<u32>, usize, usize), GameError> {
+        let img = assets.load_image(WALL_TEXTURE)?;
         let (img_width, img_height) = img.dimensions();
         let texture = img.to_rgba8().pixels().map(|p| {
             let [r, g, b, a] = p.0;
             ((a as u32) << 24) | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
         }).collect();
-        (texture, img_width as usize, img_height as usize)
+        Ok((texture, img_width as usize, img_height as usize))
     }
 
     fn lerp_color(&self, color1: u32, color2: u32, t: f64) -> u32 {
@@ -87,52 +166,214 @@ impl Renderer {
         (r << 16) | (g << 8) | b
     }
 
-    pub fn render_3d(&self, map: &Map, player: &Player, buffer: &mut Vec<u32>, z_buffer: &mut Vec<f64>) {
-        let sky_top = 0x20586d;
-        let sky_bottom = 0x9ea2ac;
-    
+    // Cada hilo dibuja un bloque de columnas contiguas en su propio pedazo de
+    // un buffer por columnas (columna x en [x*height, (x+1)*height)); al final
+    // se copia a `frame` por filas. Cada columna solo depende de su rayo, asi
+    // que el resultado es identico con cualquier numero de hilos.
+    pub fn render_3d(&mut self, map: &Map, player: &Player) {
+        let mut columns = std::mem::take(&mut self.columns);
+        let mut depth = std::mem::take(&mut self.depth);
+        let mut wall_bottoms = std::mem::take(&mut self.wall_bottoms);
+
+        let start = Instant::now();
+        self.run_pass(Pass::Walls, map, player, &mut columns, &mut depth, &mut wall_bottoms);
+        self.times.raycast = start.elapsed();
+
+        let start = Instant::now();
+        self.run_pass(Pass::Floor, map, player, &mut columns, &mut depth, &mut wall_bottoms);
         for x in 0..self.width {
-            let ray_angle = player.angle - PI / 6.0 + (x as f64 / self.width as f64) * PI / 3.0;
-            let (distance, wall_x) = self.cast_ray(map, player, ray_angle);
-    
-            let wall_height = (self.height as f64 / distance) as usize;
-            let wall_top = (self.height / 2).saturating_sub(wall_height / 2);
-            let wall_bottom = (self.height / 2 + wall_height / 2).min(self.height);
-    
-            let texture_u = wall_x;
-    
-            for y in 0..self.height {
-                let pixel_index = y * self.width + x;
-                if y < wall_top {
-                    let t = y as f64 / wall_top as f64;
-                    buffer[pixel_index] = self.color_lerp(sky_top, sky_bottom, t);
-                } else if y >= wall_top && y < wall_bottom {
-                    let v = (y as f64 - wall_top as f64) / (wall_bottom as f64 - wall_top as f64);
-                    let color = self.sample_texture(texture_u, v);
-                    let shade_factor = 1.0 / (1.0 + distance * distance * 0.1);
-                    buffer[pixel_index] = self.apply_shade(color, shade_factor);
-                    z_buffer[pixel_index] = distance;
-                } else {
-                    let t = (y - wall_bottom) as f64 / (self.height - wall_bottom) as f64;
-                    buffer[pixel_index] = self.color_lerp(0x0d798f, 0x051744, t);
+            let column = &columns[x * self.height..(x + 1) * self.height];
+            for (y, &color) in column.iter().enumerate() {
+                self.frame[y * self.width + x] = color;
+            }
+        }
+        self.times.floor = start.elapsed();
+
+        self.columns = columns;
+        self.depth = depth;
+        self.wall_bottoms = wall_bottoms;
+    }
+
+    fn run_pass(&self, pass: Pass, map: &Map, player: &Player, columns: &mut [u32], depth: &mut [f64], wall_bottoms: &mut [usize]) {
+        let threads = self.threads.clamp(1, self.width.max(1));
+        if threads == 1 {
+            self.render_columns(pass, map, player, 0, columns, depth, wall_bottoms);
+            return;
+        }
+        let per_thread = self.width.div_ceil(threads);
+        std::thread::scope(|scope| {
+            let chunks = columns.chunks_mut(per_thread * self.height)
+                .zip(depth.chunks_mut(per_thread))
+                .zip(wall_bottoms.chunks_mut(per_thread));
+            for (i, ((columns, depth), wall_bottoms)) in chunks.enumerate() {
+                scope.spawn(move || self.render_columns(pass, map, player, i * per_thread, columns, depth, wall_bottoms));
+            }
+        });
+    }
+
+    // Dibuja las columnas desde `first_x` en buffers por columnas
+    #[allow(clippy::too_many_arguments)]
+    fn render_columns(&self, pass: Pass, map: &Map, player: &Player, first_x: usize, columns: &mut [u32], depth: &mut [f64], wall_bottoms: &mut [usize]) {
+        for (i, colors) in columns.chunks_mut(self.height).enumerate() {
+            let x = first_x + i;
+            match pass {
+                Pass::Walls => {
+                    let (distance, wall_bottom) = self.render_wall_column(map, player, x, colors);
+                    depth[i] = distance;
+                    wall_bottoms[i] = wall_bottom;
                 }
+                Pass::Floor => self.render_floor_column(player, x, wall_bottoms[i], colors),
             }
         }
     }
-    
-    
 
-    fn apply_shade(&self, color: u32, factor: f64) -> u32 {
-        let r = ((color >> 16) & 0xFF) as f64 * factor;
-        let g = ((color >> 8) & 0xFF) as f64 * factor;
-        let b = (color & 0xFF) as f64 * factor;
-        ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
+    pub fn ray_angle(&self, player: &Player, x: usize) -> f64 {
+        self.view_angle(player) - self.fov / 2.0 + (x as f64 / self.width as f64) * self.fov
+    }
+
+    // Hacia donde mira la camara, con el temblor
+    fn view_angle(&self, player: &Player) -> f64 {
+        player.angle + self.camera.yaw()
+    }
+
+    // Fila del horizonte: la mitad de la pantalla movida por la inclinacion
+    // del jugador, el balanceo y el temblor. Paredes, piso y sprites se
+    // desplazan juntos (y-shearing), asi que las lineas verticales siguen rectas.
+    pub fn horizon(&self, player: &Player) -> f64 {
+        self.height as f64 * (0.5 + player.pitch + self.camera.lift())
+    }
+
+    // Cielo y pared de una columna; devuelve la distancia a la pared y la
+    // primera fila del piso
+    fn render_wall_column(&self, map: &Map, player: &Player, x: usize, colors: &mut [u32]) -> (f64, usize) {
+        let atmosphere = &self.atmosphere;
+
+        let ray_angle = self.ray_angle(player, x);
+        let (distance, wall_x) = self.cast_ray(map, player, ray_angle);
+        let (dir_x, dir_y) = (ray_angle.cos(), ray_angle.sin());
+        let offset = ray_angle - self.view_angle(player);
+        // La luz de la pared se toma un poco antes del impacto, del lado abierto
+        let hit = distance - 0.02;
+        let mut wall_light = self.lighting.sample(player.x + dir_x * hit, player.y + dir_y * hit);
+        add_light(&mut wall_light, player.flashlight.light_at(offset, distance));
+
+        let horizon = self.horizon(player);
+        let wall_height = self.height as f64 / distance;
+        let top = horizon - wall_height / 2.0;
+        let wall_top = top.clamp(0.0, self.height as f64) as usize;
+        let wall_bottom = (horizon + wall_height / 2.0).clamp(0.0, self.height as f64) as usize;
+
+        let texture_u = wall_x;
+
+        for (y, pixel) in colors.iter_mut().enumerate().take(wall_bottom) {
+            if y < wall_top {
+                let t = y as f64 / wall_top as f64;
+                *pixel = self.color_lerp(atmosphere.sky_top, atmosphere.sky_bottom, t);
+            } else {
+                // Con la pared cortada por el borde la textura sigue en su lugar
+                let v = ((y as f64 - top) / wall_height).clamp(0.0, 1.0);
+                let color = self.sample_texture(texture_u, v);
+                *pixel = atmosphere.shade(color, wall_light, distance);
+            }
+        }
+        (distance, wall_bottom)
+    }
+
+    fn render_floor_column(&self, player: &Player, x: usize, wall_bottom: usize, colors: &mut [u32]) {
+        let atmosphere = &self.atmosphere;
+        let horizon = self.horizon(player);
+        // Altura de los ojos en pixeles: no cambia al inclinar la vista
+        let eye_height = self.height as f64 / 2.0;
+
+        let ray_angle = self.ray_angle(player, x);
+        let (dir_x, dir_y) = (ray_angle.cos(), ray_angle.sin());
+        let offset = ray_angle - self.view_angle(player);
+
+        for (y, pixel) in colors.iter_mut().enumerate().skip(wall_bottom) {
+            let t = (y - wall_bottom) as f64 / (self.height - wall_bottom) as f64;
+            let color = self.color_lerp(atmosphere.floor_far, atmosphere.floor_near, t);
+            // Distancia del punto del piso que se ve en esta fila
+            let floor_distance = eye_height / (y as f64 + 0.5 - horizon);
+            let mut light = self.lighting.sample(player.x + dir_x * floor_distance, player.y + dir_y * floor_distance);
+            add_light(&mut light, player.flashlight.light_at(offset, floor_distance));
+            *pixel = if atmosphere.fog_floor {
+                atmosphere.shade(color, light, floor_distance)
+            } else {
+                atmosphere.light(color, light)
+            };
+        }
+    }
+
+    // Dibuja un sprite parado en (x, y) del mundo, detras de las paredes mas
+    // cercanas, con la luz y la niebla del nivel
+    pub fn render_sprite(&mut self, player: &Player, texture: &RgbaImage, x: f64, y: f64) {
+        let (width, height) = (self.width, self.height);
+        let dx = x - player.x;
+        let dy = y - player.y;
+
+        let distance = (dx * dx + dy * dy).sqrt();
+        // Angulo respecto a la vista, entre -PI y PI
+        let angle = (dy.atan2(dx) - self.view_angle(player) + PI * 3.0).rem_euclid(PI * 2.0) - PI;
+        let half_fov = self.fov / 2.0;
+
+        let (tex_width, tex_height) = texture.dimensions();
+        if distance <= 0.5 || angle.abs() >= half_fov || tex_width == 0 || tex_height == 0 {
+            return;
+        }
+
+        let light = self.light_at(player, x, y);
+        let atmosphere = &self.atmosphere;
+        let sprite_size = (height as f64 / distance) as usize;
+        let horizon = self.horizon(player) as i64;
+        let h_offset = ((angle / half_fov + 1.0) * width as f64 / 2.0) as i32;
+
+        for sx in 0..sprite_size {
+            let screen_x = h_offset + sx as i32 - (sprite_size / 2) as i32;
+            if screen_x < 0 || screen_x >= width as i32 || self.depth[screen_x as usize] <= distance {
+                continue;
+            }
+            let tex_x = (sx as f64 / sprite_size as f64 * tex_width as f64) as u32;
+
+            for sy in 0..sprite_size {
+                let screen_y = horizon + sy as i64 - (sprite_size / 2) as i64;
+                if screen_y < 0 {
+                    continue;
+                }
+                if screen_y >= height as i64 {
+                    break;
+                }
+
+                let tex_y = (sy as f64 / sprite_size as f64 * tex_height as f64) as u32;
+                let [r, g, b, a] = texture.get_pixel(tex_x, tex_y).0;
+                if a == 0 {
+                    continue;
+                }
+                let color = ((r as u32) << 16) | ((g as u32) << 8) | b as u32;
+                let color = if atmosphere.fog_sprites {
+                    atmosphere.shade(color, light, distance)
+                } else {
+                    atmosphere.light(color, light)
+                };
+                let lit = Rgba([(color >> 16) as u8, (color >> 8) as u8, color as u8, a]);
+                let index = screen_y as usize * width + screen_x as usize;
+                self.frame[index] = composite::blend_pixel(self.frame[index], &lit);
+            }
+        }
+    }
+
+    // Luz de las fuentes del nivel y de la linterna en un punto (para sprites)
+    fn light_at(&self, player: &Player, x: f64, y: f64) -> [f64; 3] {
+        let mut light = self.lighting.sample(x, y);
+        let distance = ((x - player.x).powi(2) + (y - player.y).powi(2)).sqrt();
+        let offset = ((y - player.y).atan2(x - player.x) - self.view_angle(player) + PI * 3.0).rem_euclid(PI * 2.0) - PI;
+        add_light(&mut light, player.flashlight.light_at(offset, distance));
+        light
     }
 
     fn cast_ray(&self, map: &Map, player: &Player, angle: f64) -> (f64, f64) {
         let mut x = player.x;
         let mut y = player.y;
-        let step_size = 0.01;
+        let step_size = RAY_STEP;
         let dx = angle.cos() * step_size;
         let dy = angle.sin() * step_size;
 
@@ -149,9 +390,19 @@ impl Renderer {
     }
 
     
-    pub fn render_minimap(&self, map: &Map, player: &Player, enemy: &Enemy, buffer: &mut Vec<u32>) {
-        let minimap_size = 140;
-        let scale = minimap_size as f64 / map.width() as f64;
+    // Lado en pixeles del minimapa (esquina superior izquierda) y pixeles por celda
+    pub fn minimap_size(&self) -> usize {
+        (self.height / 4).min(self.width / 4)
+    }
+
+    pub fn minimap_scale(&self, map: &Map) -> f64 {
+        self.minimap_size() as f64 / map.width() as f64
+    }
+
+    pub fn render_minimap(&mut self, map: &Map, player: &Player, enemies: &[Enemy]) {
+        let minimap_size = self.minimap_size();
+        let scale = self.minimap_scale(map);
+        let buffer = &mut self.frame;
 
         for y in 0..minimap_size {
             for x in 0..minimap_size {
@@ -165,7 +416,7 @@ impl Renderer {
                         'P' => 0x46A1C9,
                         _ => 0x000000,
                     };
-                    let pixel_index = (y * self.width + x) as usize;
+                    let pixel_index = y * self.width + x;
                     buffer[pixel_index] = color;
                 }
             }
@@ -176,38 +427,93 @@ impl Renderer {
 
         for dy in 0..3 {
             for dx in 0..3 {
-                let pixel_index = ((player_y + dy) * self.width + (player_x + dx)) as usize;
+                let pixel_index = (player_y + dy) * self.width + (player_x + dx);
                 if pixel_index < buffer.len() {
                     buffer[pixel_index] = 0xFF0000;
                 }
             }
         }
         
-        let enemy_x = (enemy.x * scale) as usize;
-        let enemy_y = (enemy.y * scale) as usize;
+        for enemy in enemies {
+            let enemy_x = (enemy.x * scale) as usize;
+            let enemy_y = (enemy.y * scale) as usize;
 
-        for dy in 0..3 {
-            for dx in 0..3 {
-                let pixel_index = ((enemy_y + dy) * self.width + (enemy_x + dx)) as usize;
-                if pixel_index < buffer.len() {
-                    buffer[pixel_index] = 0x00FF00; // Color verde para el enemigo
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    let pixel_index = (enemy_y + dy) * self.width + (enemy_x + dx);
+                    if pixel_index < buffer.len() {
+                        buffer[pixel_index] = 0x00FF00; // Color verde para el enemigo
+                    }
                 }
             }
         }
     }
     fn color_lerp(&self, start: u32, end: u32, t: f64) -> u32 {
-        let r1 = (start >> 16) & 0xFF;
-        let g1 = (start >> 8) & 0xFF;
-        let b1 = start & 0xFF;
+        self.lerp_color(start, end, t)
+    }
+}
 
-        let r2 = (end >> 16) & 0xFF;
-        let g2 = (end >> 8) & 0xFF;
-        let b2 = end & 0xFF;
+fn add_light(total: &mut [f64; 3], light: [f64; 3]) {
+    for c in 0..3 {
+        total[c] += light[c];
+    }
+}
 
-        let r = (r1 as f64 + (r2 as f64 - r1 as f64) * t) as u32;
-        let g = (g1 as f64 + (g2 as f64 - g1 as f64) * t) as u32;
-        let b = (b1 as f64 + (b2 as f64 - b1 as f64) * t) as u32;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::BASE_DIR;
 
-        (r << 16) | (g << 8) | b
+    #[test]
+    fn threaded_render_matches_single_thread() {
+        let assets = Assets::new(BASE_DIR);
+        let map = Map::load(&assets, "maze.txt").unwrap();
+        let config = Map::level_config(&assets, "maze.txt").unwrap();
+        let mut player = Player::new(&map);
+        player.angle = 0.7;
+        player.pitch = 0.1;
+
+        // (ancho, alto, hilos): parejo, con resto y con mas hilos que columnas
+        for (width, height, threads) in [(320, 200, 4), (317, 200, 3), (5, 40, 8)] {
+            let atmosphere = Atmosphere::from_config(&config).unwrap();
+            let lighting = Lighting::from_config(&config, &map).unwrap();
+            let mut renderer = Renderer::new(width, height, &assets, atmosphere, lighting).unwrap();
+            renderer.threads = 1;
+            renderer.render_3d(&map, &player);
+            let (frame, depth) = (renderer.frame.clone(), renderer.depth.clone());
+            renderer.threads = threads;
+            renderer.render_3d(&map, &player);
+
+            assert!(frame == renderer.frame, "{}x{} on {} threads", width, height, threads);
+            let bits = |depth: &[f64]| depth.iter().map(|d| d.to_bits()).collect::<Vec<_>>();
+            assert_eq!(bits(&depth), bits(&renderer.depth), "{}x{} on {} threads", width, height, threads);
+        }
+    }
+
+    #[test]
+    fn flashlight_follows_the_shaken_view() {
+        let assets = Assets::new(BASE_DIR);
+        let map = Map::load(&assets, "maze.txt").unwrap();
+        let config = Map::level_config(&assets, "maze.txt").unwrap();
+        let atmosphere = Atmosphere::from_config(&config).unwrap();
+        let lighting = Lighting::from_config(&config, &map).unwrap();
+        let mut renderer = Renderer::new(64, 48, &assets, atmosphere, lighting).unwrap();
+        let player = Player::new(&map);
+        renderer.camera.shake(1.0);
+        let yaw = renderer.view_angle(&player) - player.angle;
+        assert!(yaw.abs() > 0.01);
+
+        // Un punto justo dentro del borde del cono (medio angulo 0.4) del lado
+        // hacia donde gira el temblor: sin contar el temblor quedaria afuera
+        let offset = 0.39 * yaw.signum();
+        let angle = player.angle + yaw + offset;
+        let (x, y) = (player.x + angle.cos(), player.y + angle.sin());
+        let ambient = renderer.lighting.sample(x, y);
+        let expected = player.flashlight.light_at(offset, 1.0);
+        let light = renderer.light_at(&player, x, y);
+        assert!(expected[0] > 0.0);
+        for channel in 0..3 {
+            assert!((light[channel] - ambient[channel] - expected[channel]).abs() < 1e-9);
+        }
     }
 }
@@ -0,0 +1,138 @@
+use std::fs;
+
+use crate::input::Input;
+
+// Una partida grabada: el mapa y, por cuadro, el dt y la entrada del jugador
+// (mas los comandos de consola que se ejecutaron). La simulacion solo depende
+// de eso, asi que repetirla da el mismo resultado.
+//
+//   # replay 2
+//   map maze.txt
+//   seed 42                  # solo si el mapa se genero con --seed
+//   frame 0.066667 W--D--- 0.0125 -0.003
+//   command noclip
+//
+// Las siete letras del cuadro son adelante, atras, izquierda, derecha,
+// linterna, correr y agacharse (W S A D F R C); '-' si no se presiono. Siguen
+// el giro y la inclinacion vertical del cuadro, en radianes.
+pub struct Recording {
+    pub map: String,
+    pub seed: Option<u64>,
+    pub frames: Vec<Frame>,
+}
+
+pub struct Frame {
+    pub dt: f64,
+    pub input: Input,
+    pub commands: Vec<String>,
+}
+
+const HEADER: &str = "# replay 2";
+const KEYS: [char; 7] = ['W', 'S', 'A', 'D', 'F', 'R', 'C'];
+
+impl Recording {
+    pub fn new(map: &str, seed: Option<u64>) -> Self {
+        Recording { map: map.to_string(), seed, frames: Vec::new() }
+    }
+
+    pub fn push(&mut self, dt: f64, input: Input) {
+        self.frames.push(Frame { dt, input, commands: Vec::new() });
+    }
+
+    // El comando se ejecuto despues de la entrada del ultimo cuadro
+    pub fn push_command(&mut self, line: &str) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.commands.push(line.to_string());
+        }
+    }
+
+    pub fn load(filename: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(filename)
+            .map_err(|e| format!("Failed to read replay '{}': {}", filename, e))?;
+        Recording::parse(&text).map_err(|e| format!("{}: {}", filename, e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate();
+        if lines.next().map(|(_, line)| line.trim()) != Some(HEADER) {
+            return Err(format!("not a replay (expected '{}' on the first line)", HEADER));
+        }
+        let mut recording = Recording::new("", None);
+        for (number, line) in lines {
+            let error = |message: &str| format!("line {}: {}", number + 1, message);
+            let (kind, rest) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+            match kind {
+                "" => {}
+                "map" => recording.map = rest.to_string(),
+                "seed" => recording.seed = Some(rest.parse().map_err(|_| error("invalid seed"))?),
+                "frame" => {
+                    let fields: Vec<&str> = rest.split_whitespace().collect();
+                    let [dt, keys, turn, pitch] = fields[..] else {
+                        return Err(error("expected 'frame DT KEYS TURN PITCH'"));
+                    };
+                    let dt = dt.parse().map_err(|_| error("invalid dt"))?;
+                    let turn = turn.parse().map_err(|_| error("invalid turn"))?;
+                    let pitch = pitch.parse().map_err(|_| error("invalid pitch"))?;
+                    let keys: Vec<bool> = keys.chars().map(|c| c != '-').collect();
+                    let [forward, backward, left, right, flashlight, sprint, crouch] = keys[..] else {
+                        return Err(error("keys must have 7 letters (WSADFRC, '-' for released)"));
+                    };
+                    let input = Input { forward, backward, left, right, turn, pitch, flashlight, sprint, crouch };
+                    recording.push(dt, input);
+                }
+                "command" if recording.frames.is_empty() => return Err(error("command before the first frame")),
+                "command" => recording.push_command(rest),
+                _ => return Err(error(&format!("unknown entry '{}'", kind))),
+            }
+        }
+        if recording.map.is_empty() {
+            return Err("missing 'map' line".to_string());
+        }
+        Ok(recording)
+    }
+
+    pub fn save(&self, filename: &str) -> Result<(), String> {
+        let mut text = format!("{}\nmap {}\n", HEADER, self.map);
+        if let Some(seed) = self.seed {
+            text += &format!("seed {}\n", seed);
+        }
+        for frame in &self.frames {
+            let input = &frame.input;
+            let pressed = [input.forward, input.backward, input.left, input.right, input.flashlight, input.sprint, input.crouch];
+            let keys: String = pressed.iter().zip(KEYS)
+                .map(|(&down, letter)| if down { letter } else { '-' })
+                .collect();
+            text += &format!("frame {} {} {} {}\n", frame.dt, keys, input.turn, input.pitch);
+            for command in &frame.commands {
+                text += &format!("command {}\n", command);
+            }
+        }
+        fs::write(filename, text).map_err(|e| format!("Failed to write replay '{}': {}", filename, e))
+    }
+}
+
+// Entrega los cuadros de una grabacion en orden
+pub struct Playback {
+    recording: Recording,
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        Playback { recording, next: 0 }
+    }
+
+    pub fn next_frame(&mut self) -> Option<&Frame> {
+        let frame = self.recording.frames.get(self.next)?;
+        self.next += 1;
+        Some(frame)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next >= self.recording.frames.len()
+    }
+
+    pub fn frames_played(&self) -> usize {
+        self.next
+    }
+}
@@ -0,0 +1,109 @@
+use rodio::Source;
+use std::f64::consts::FRAC_PI_4;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::player::Player;
+
+// Distancia (en celdas) a partir de la cual un sonido deja de oirse
+const MAX_DISTANCE: f64 = 12.0;
+const ROLLOFF: f64 = 0.6;
+
+// Ganancia por canal compartida entre el juego y el hilo de audio
+pub struct Gains {
+    left: AtomicU32,
+    right: AtomicU32,
+}
+
+impl Gains {
+    pub fn new(left: f32, right: f32) -> Arc<Self> {
+        Arc::new(Gains { left: AtomicU32::new(left.to_bits()), right: AtomicU32::new(right.to_bits()) })
+    }
+
+    pub fn set(&self, (left, right): (f32, f32)) {
+        self.left.store(left.to_bits(), Ordering::Relaxed);
+        self.right.store(right.to_bits(), Ordering::Relaxed);
+    }
+
+    fn left(&self) -> f32 {
+        f32::from_bits(self.left.load(Ordering::Relaxed))
+    }
+
+    fn right(&self) -> f32 {
+        f32::from_bits(self.right.load(Ordering::Relaxed))
+    }
+}
+
+// Paneo de potencia constante y atenuacion por distancia de un sonido en
+// `(x, y)` respecto a la posicion y el angulo del jugador.
+pub fn spatialize(player: &Player, x: f64, y: f64) -> (f32, f32) {
+    let dx = x - player.x;
+    let dy = y - player.y;
+    let distance = (dx * dx + dy * dy).sqrt();
+    if distance >= MAX_DISTANCE {
+        return (0.0, 0.0);
+    }
+
+    let attenuation = 1.0 / (1.0 + ROLLOFF * distance * distance) * (1.0 - distance / MAX_DISTANCE);
+
+    // -1 = izquierda, 1 = derecha; el lado derecho es `angle + PI/2`
+    let pan = if distance > 0.01 { (dy.atan2(dx) - player.angle).sin() } else { 0.0 };
+    let theta = (pan + 1.0) * FRAC_PI_4;
+
+    ((theta.cos() * attenuation) as f32, (theta.sin() * attenuation) as f32)
+}
+
+// Convierte cualquier fuente a estereo aplicando las ganancias de `Gains`
+pub struct Panned<S: Source<Item = f32>> {
+    input: S,
+    gains: Arc<Gains>,
+    pending_right: Option<f32>,
+    channel: u16,
+}
+
+impl<S: Source<Item = f32>> Panned<S> {
+    pub fn new(input: S, gains: Arc<Gains>) -> Self {
+        Panned { input, gains, pending_right: None, channel: 0 }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for Panned<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if let Some(sample) = self.pending_right.take() {
+            return Some(sample * self.gains.right());
+        }
+
+        let sample = self.input.next()?;
+        let channels = self.input.channels();
+        if channels == 1 {
+            self.pending_right = Some(sample);
+            return Some(sample * self.gains.left());
+        }
+
+        let gain = if self.channel.is_multiple_of(2) { self.gains.left() } else { self.gains.right() };
+        self.channel = (self.channel + 1) % channels;
+        Some(sample * gain)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Panned<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        let len = self.input.current_frame_len()?;
+        Some(if self.input.channels() == 1 { len * 2 } else { len })
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels().max(2)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
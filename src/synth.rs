@@ -0,0 +1,77 @@
+use std::f32::consts::PI;
+
+use rodio::buffer::SamplesBuffer;
+
+const SAMPLE_RATE: u32 = 22050;
+// Latidos por buffer; en un cue con loop el backend lo repite sin cortes
+const BEATS: usize = 8;
+// Frecuencia del golpe grave y cuanto tarda en apagarse
+const THUMP_HZ: f32 = 52.0;
+const THUMP_DECAY: f32 = 16.0;
+// Frecuencia base del gruñido del enemigo
+const GROWL_HZ: f32 = 68.0;
+
+// Sonidos generados por codigo para los cues cuyo archivo no viene en los
+// assets base. Un paquete que trae el archivo lo reemplaza.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Synth {
+    // Latido "lub-dub" a `bpm` pulsaciones por minuto
+    Heartbeat { bpm: f32 },
+    // Gruñido grave que sube y baja, de `seconds` de largo
+    Growl { seconds: f32 },
+    // Pitido corto de `hz` que se apaga en `seconds`, para menus y consola
+    Blip { hz: f32, seconds: f32 },
+}
+
+impl Synth {
+    // Muestras mono entre -1 y 1 a `SAMPLE_RATE`
+    pub fn samples(self) -> Vec<f32> {
+        match self {
+            Synth::Heartbeat { bpm } => {
+                let period = 60.0 / bpm;
+                let beat_len = (period * SAMPLE_RATE as f32) as usize;
+                let mut samples = vec![0.0; beat_len * BEATS];
+                for (i, sample) in samples.iter_mut().enumerate() {
+                    let t = (i % beat_len) as f32 / SAMPLE_RATE as f32;
+                    // El segundo golpe llega a un cuarto del periodo, mas suave
+                    *sample = thump(t) * 0.8 + thump(t - period * 0.25) * 0.5;
+                }
+                samples
+            }
+            Synth::Growl { seconds } => {
+                let len = (seconds * SAMPLE_RATE as f32) as usize;
+                let mut phase = 0.0;
+                (0..len)
+                    .map(|i| {
+                        let t = i as f32 / SAMPLE_RATE as f32;
+                        // Diente de sierra con vibrato, que entra y sale suave
+                        let frequency = GROWL_HZ + 12.0 * (2.0 * PI * 5.0 * t).sin();
+                        phase = (phase + frequency / SAMPLE_RATE as f32).fract();
+                        let envelope = (PI * t / seconds).sin().powi(2);
+                        (phase * 2.0 - 1.0) * envelope * 0.5
+                    })
+                    .collect()
+            }
+            Synth::Blip { hz, seconds } => {
+                let len = (seconds * SAMPLE_RATE as f32) as usize;
+                (0..len)
+                    .map(|i| {
+                        let t = i as f32 / SAMPLE_RATE as f32;
+                        (2.0 * PI * hz * t).sin() * (1.0 - t / seconds) * 0.4
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    pub fn source(self) -> SamplesBuffer<f32> {
+        SamplesBuffer::new(1, SAMPLE_RATE, self.samples())
+    }
+}
+
+fn thump(t: f32) -> f32 {
+    if t < 0.0 {
+        return 0.0;
+    }
+    (2.0 * PI * THUMP_HZ * t).sin() * (-t * THUMP_DECAY).exp()
+}
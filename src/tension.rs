@@ -0,0 +1,150 @@
+use crate::enemy::{Enemy, EnemyState};
+use crate::player::Player;
+
+// A partir de esta distancia el enemigo ya no aporta tension
+const PROXIMITY_RANGE: f64 = 10.0;
+// Velocidad (por segundo) con la que la tension sube y baja
+const RISE_RATE: f32 = 1.5;
+const FALL_RATE: f32 = 0.25;
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum TensionLevel {
+    Calm,
+    Alert,
+    Chase,
+}
+
+// Umbrales (subir, bajar) de cada nivel; la diferencia es la histeresis que
+// evita que la musica cambie una y otra vez cerca del limite.
+const ALERT_THRESHOLDS: (f32, f32) = (0.35, 0.2);
+const CHASE_THRESHOLDS: (f32, f32) = (0.75, 0.55);
+
+// Nivel de tension del juego (0 = tranquilo, 1 = persecucion cercana)
+pub struct Tension {
+    value: f32,
+    level: TensionLevel,
+}
+
+impl Default for Tension {
+    fn default() -> Self {
+        Tension::new()
+    }
+}
+
+impl Tension {
+    pub fn new() -> Self {
+        Tension { value: 0.0, level: TensionLevel::Calm }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn level(&self) -> TensionLevel {
+        self.level
+    }
+
+    // La tension la pone el enemigo mas amenazante
+    pub fn update(&mut self, enemies: &[Enemy], player: &Player, dt: f64) {
+        let target = enemies.iter().map(|enemy| threat(enemy, player)).fold(0.0, f32::max);
+        self.approach(target, dt);
+    }
+
+    // Acerca el valor al objetivo (sube rapido, baja lento) y cambia de nivel
+    // solo al cruzar el umbral de subida o de bajada
+    fn approach(&mut self, target: f32, dt: f64) {
+        let rate = if target > self.value { RISE_RATE } else { FALL_RATE };
+        let max_step = rate * dt as f32;
+        self.value += (target - self.value).clamp(-max_step, max_step);
+
+        self.level = match self.level {
+            TensionLevel::Calm if self.value >= CHASE_THRESHOLDS.0 => TensionLevel::Chase,
+            TensionLevel::Calm if self.value >= ALERT_THRESHOLDS.0 => TensionLevel::Alert,
+            TensionLevel::Alert if self.value >= CHASE_THRESHOLDS.0 => TensionLevel::Chase,
+            TensionLevel::Alert if self.value < ALERT_THRESHOLDS.1 => TensionLevel::Calm,
+            TensionLevel::Chase if self.value < ALERT_THRESHOLDS.1 => TensionLevel::Calm,
+            TensionLevel::Chase if self.value < CHASE_THRESHOLDS.1 => TensionLevel::Alert,
+            level => level,
+        };
+    }
+}
+
+// Tension que aporta un enemigo: crece al acercarse y una persecucion nunca
+// baja de 0.6 aunque este lejos
+fn threat(enemy: &Enemy, player: &Player) -> f32 {
+    let proximity = (1.0 - enemy.distance_to(player) / PROXIMITY_RANGE).clamp(0.0, 1.0) as f32;
+    match enemy.state {
+        EnemyState::Chasing => 0.6 + 0.4 * proximity,
+        EnemyState::Idle => 0.7 * proximity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::{Assets, BASE_DIR};
+
+    // Lleva el valor a `target` de un solo paso
+    fn settle(tension: &mut Tension, target: f32) {
+        tension.approach(target, 100.0);
+        assert!((tension.value() - target).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rises_fast_and_falls_slowly() {
+        let mut tension = Tension::new();
+        tension.approach(1.0, 0.1);
+        assert!((tension.value() - RISE_RATE * 0.1).abs() < 1e-6);
+        settle(&mut tension, 1.0);
+        tension.approach(0.0, 0.1);
+        assert!((tension.value() - (1.0 - FALL_RATE * 0.1)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn alert_level_has_hysteresis() {
+        let mut tension = Tension::new();
+        settle(&mut tension, 0.3);
+        assert_eq!(tension.level(), TensionLevel::Calm);
+        settle(&mut tension, 0.4);
+        assert_eq!(tension.level(), TensionLevel::Alert);
+        // Entre el umbral de bajada y el de subida se queda donde estaba
+        settle(&mut tension, 0.25);
+        assert_eq!(tension.level(), TensionLevel::Alert);
+        settle(&mut tension, 0.1);
+        assert_eq!(tension.level(), TensionLevel::Calm);
+        settle(&mut tension, 0.25);
+        assert_eq!(tension.level(), TensionLevel::Calm);
+    }
+
+    #[test]
+    fn chase_level_has_hysteresis() {
+        let mut tension = Tension::new();
+        settle(&mut tension, 0.8);
+        assert_eq!(tension.level(), TensionLevel::Chase);
+        settle(&mut tension, 0.6);
+        assert_eq!(tension.level(), TensionLevel::Chase);
+        settle(&mut tension, 0.5);
+        assert_eq!(tension.level(), TensionLevel::Alert);
+        settle(&mut tension, 0.7);
+        assert_eq!(tension.level(), TensionLevel::Alert);
+        settle(&mut tension, 0.1);
+        assert_eq!(tension.level(), TensionLevel::Calm);
+    }
+
+    #[test]
+    fn chasing_enemies_raise_more_tension_than_idle_ones() {
+        let assets = Assets::new(BASE_DIR);
+        let player = Player::at(1.5, 1.5);
+        let mut enemy = Enemy::at(6.5, 1.5, &assets);
+        assert!((threat(&enemy, &player) - 0.35).abs() < 1e-6);
+
+        enemy.state = EnemyState::Chasing;
+        assert!((threat(&enemy, &player) - 0.8).abs() < 1e-6);
+
+        // Fuera de rango solo cuenta la persecucion
+        enemy.x = 30.0;
+        assert_eq!(threat(&enemy, &player), 0.6);
+        enemy.state = EnemyState::Idle;
+        assert_eq!(threat(&enemy, &player), 0.0);
+    }
+}
@@ -1,5 +1,10 @@
-use minifb::Window;
 use image::RgbaImage;
+use crate::assets::Assets;
+use crate::composite;
+use crate::error::GameError;
+use crate::health::{Health, MAX_HEALTH};
+use crate::lighting::{Flashlight, BATTERY_LOW};
+use crate::movement::{MoveMode, Movement};
 
 pub struct UI {
     welcome_image: RgbaImage,
@@ -8,43 +13,53 @@ pub struct UI {
 }
 
 impl UI {
-    pub fn new() -> Self {
-        let welcome_image = image::open("assets/welcome.png").unwrap().to_rgba8();
-        let victory_image = image::open("assets/victory.png").unwrap().to_rgba8();
-        let game_over_image = image::open("assets/gameover.png").unwrap().to_rgba8();
-        UI { welcome_image, victory_image, game_over_image }
+    pub fn new(assets: &Assets) -> Result<Self, GameError> {
+        let welcome_image = assets.load_image("welcome.png")?.to_rgba8();
+        let victory_image = assets.load_image("victory.png")?.to_rgba8();
+        let game_over_image = assets.load_image("gameover.png")?.to_rgba8();
+        Ok(UI { welcome_image, victory_image, game_over_image })
     }
 
-    pub fn show_welcome_screen(&self, window: &mut Window) {
-        self.show_image(window, &self.welcome_image);
+    // Sin imagenes, solo para texto: sirve aunque no se haya podido cargar nada
+    pub fn text_only() -> Self {
+        let empty = RgbaImage::new(1, 1);
+        UI { welcome_image: empty.clone(), victory_image: empty.clone(), game_over_image: empty }
     }
 
-    pub fn show_victory_screen(&self, window: &mut Window) {
-        self.show_image(window, &self.victory_image);
+    pub fn draw_welcome_screen(&self, buffer: &mut [u32], width: usize, height: usize) {
+        self.draw_screen_image(buffer, width, height, &self.welcome_image);
     }
 
-    pub fn show_game_over_screen(&self, window: &mut Window) {
-        self.show_image(window, &self.game_over_image);
+    pub fn draw_victory_screen(&self, buffer: &mut [u32], width: usize, height: usize) {
+        self.draw_screen_image(buffer, width, height, &self.victory_image);
     }
 
-    fn show_image(&self, window: &mut Window, image: &RgbaImage) {
-        let (width, height) = window.get_size();
-        let mut buffer = vec![0; width * height];
-        
-        let resized_image = image::imageops::resize(image, width as u32, height as u32, image::imageops::FilterType::Nearest);
+    pub fn draw_game_over_screen(&self, buffer: &mut [u32], width: usize, height: usize) {
+        self.draw_screen_image(buffer, width, height, &self.game_over_image);
+    }
+
+    // Pantalla de texto sobre fondo negro (menu de opciones, mensajes)
+    pub fn draw_text_screen(&self, buffer: &mut [u32], width: usize, title: &str, lines: &[String]) {
+        buffer.fill(0);
+        self.draw_text_scaled(buffer, width, title, 20, 20, 0xFFFFFF, 2);
+        for (i, line) in lines.iter().enumerate() {
+            self.draw_text(buffer, width, line, 20, 60 + i * 12, 0xC8C8C8);
+        }
+    }
 
-        self.draw_image(&mut buffer, width, &resized_image);
-        
-        window.update_with_buffer(&buffer, width, height).unwrap();
+    // Estira la imagen a toda la resolucion de render
+    fn draw_screen_image(&self, buffer: &mut [u32], width: usize, height: usize, image: &RgbaImage) {
+        buffer.fill(0);
+        composite::blit_stretched(buffer, width, height, image);
     }
 
     pub fn render_fps(&self, fps: u32, buffer: &mut [u32], width: usize, height: usize) {
         let fps_text = format!("FPS: {}", fps);
         let text_color = 0xFFFFFF; 
         let background_color = 0x000000; 
-        let scale = 2; // Aumenta este valor para hacer el texto más grande
+        let scale = (height / 290).max(1); // Crece con la resolucion de render
     
-        let x = width - (fps_text.len() * 8 * scale) - 10; 
+        let x = width.saturating_sub(fps_text.len() * 8 * scale + 10); 
         let y = 10; 
     
         for (i, c) in fps_text.chars().enumerate() {
@@ -52,6 +67,94 @@ impl UI {
         }
     }
     
+    // Borde rojo que crece y se intensifica con la tension del juego
+    pub fn render_tension(&self, tension: f32, buffer: &mut [u32], width: usize, height: usize) {
+        let border = ((tension * height as f32 * 0.08) as usize).min(width / 2).min(height / 2);
+        if border == 0 {
+            return;
+        }
+
+        for y in 0..height {
+            let edge_y = y.min(height - 1 - y);
+            // En las filas del centro solo se pintan los bordes laterales
+            let (left, right) = if edge_y < border {
+                (0..width, 0..0)
+            } else {
+                (0..border, width - border..width)
+            };
+            for x in left.chain(right) {
+                let edge = x.min(width - 1 - x).min(edge_y);
+                let t = (1.0 - edge as f32 / border as f32) * tension * 0.7;
+                let index = y * width + x;
+                buffer[index] = blend(buffer[index], 0xB00000, t);
+            }
+        }
+    }
+
+    // Bateria de la linterna, abajo a la derecha
+    pub fn render_battery(&self, flashlight: &Flashlight, buffer: &mut [u32], width: usize, height: usize) {
+        let percent = (flashlight.battery * 100.0).ceil() as u32;
+        let state = if flashlight.on { "" } else { " OFF" };
+        let text = format!("LIGHT {}%{}", percent, state);
+        let color = if flashlight.battery < BATTERY_LOW { 0xFF4040 } else { 0xFFFFFF };
+        let x = width.saturating_sub(text.len() * 8 + 10);
+        self.draw_text(buffer, width, &text, x, height.saturating_sub(18), color);
+    }
+
+    // Salud y vidas sobre la stamina
+    pub fn render_health(&self, health: &Health, buffer: &mut [u32], width: usize, height: usize) {
+        let text = format!("HEALTH {} LIVES {}", health.current.ceil() as u32, health.lives);
+        let color = if health.current <= MAX_HEALTH / 3.0 { 0xFF4040 } else { 0xFFFFFF };
+        let x = width.saturating_sub(text.len() * 8 + 10);
+        self.draw_text(buffer, width, &text, x, height.saturating_sub(42), color);
+    }
+
+    // Destello rojo al recibir un golpe, mas fuerte en los bordes; con poca
+    // salud queda un resto tenue
+    pub fn render_damage(&self, health: &Health, buffer: &mut [u32], width: usize, height: usize) {
+        let wounded = (1.0 - health.current / MAX_HEALTH) * 0.3;
+        let amount = health.hurt_flash().max(wounded) as f32;
+        if amount <= 0.0 {
+            return;
+        }
+        let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+        let radius = (cx * cx + cy * cy).sqrt();
+        for y in 0..height {
+            for x in 0..width {
+                let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+                let edge = (dx * dx + dy * dy).sqrt() / radius;
+                let index = y * width + x;
+                buffer[index] = blend(buffer[index], 0x900000, amount * (0.15 + 0.6 * edge * edge));
+            }
+        }
+    }
+
+    // Stamina sobre la bateria; en rojo mientras el jugador esta agotado
+    pub fn render_stamina(&self, movement: &Movement, buffer: &mut [u32], width: usize, height: usize) {
+        let percent = (movement.stamina * 100.0).ceil() as u32;
+        let mode = if movement.mode == MoveMode::Crouch { " CROUCH" } else { "" };
+        let text = format!("STAMINA {}%{}", percent, mode);
+        let color = if movement.exhausted { 0xFF4040 } else { 0xFFFFFF };
+        let x = width.saturating_sub(text.len() * 8 + 10);
+        self.draw_text(buffer, width, &text, x, height.saturating_sub(30), color);
+    }
+
+    // Franja roja sobre la bateria con el error de la ultima recarga
+    pub fn render_error(&self, message: &str, buffer: &mut [u32], width: usize, height: usize) {
+        let columns = (width.saturating_sub(20) / 8).max(1);
+        let lines: Vec<&str> = message.lines().collect();
+        let bottom = height.saturating_sub(24);
+        let top = bottom.saturating_sub(lines.len() * 10 + 4);
+        for pixel in &mut buffer[top * width..bottom * width] {
+            *pixel = blend(*pixel, 0x600000, 0.8);
+        }
+        for (i, line) in lines.iter().enumerate() {
+            let line: String = line.chars().take(columns).collect();
+            self.draw_text(buffer, width, &line, 10, top + 3 + i * 10, 0xFFFFFF);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn draw_char_scaled(&self, buffer: &mut [u32], width: usize, c: char, x: usize, y: usize, color: u32, bg_color: u32, scale: usize) {
         let font_char = get_font_data(c);
         for (dy, &row) in font_char.iter().enumerate() {
@@ -70,6 +173,7 @@ impl UI {
         }
     }
     
+    #[allow(clippy::too_many_arguments)]
     fn draw_char(&self, buffer: &mut [u32], width: usize, c: char, x: usize, y: usize, color: u32, bg_color: u32) {
         let font_char = get_font_data(c);
         for (dy, &row) in font_char.iter().enumerate() {
@@ -84,22 +188,29 @@ impl UI {
         }
     }
 
-    fn draw_image(&self, buffer: &mut Vec<u32>, width: usize, image: &RgbaImage) {
-        for (x, y, pixel) in image.enumerate_pixels() {
-            if x < width as u32 && y < (buffer.len() / width) as u32 {
-                let index = y as usize * width + x as usize;
-                buffer[index] = ((pixel[3] as u32) << 24) | ((pixel[0] as u32) << 16) | ((pixel[1] as u32) << 8) | (pixel[2] as u32);
-            }
+    pub fn draw_text(&self, buffer: &mut [u32], width: usize, text: &str, x: usize, y: usize, color: u32) {
+        for (i, c) in text.chars().enumerate() {
+            self.draw_char(buffer, width, c.to_ascii_uppercase(), x + i * 8, y, color, 0); 
         }
     }
 
-    fn draw_text(&self, buffer: &mut Vec<u32>, width: usize, text: &str, x: usize, y: usize, color: u32) {
+    #[allow(clippy::too_many_arguments)]
+    fn draw_text_scaled(&self, buffer: &mut [u32], width: usize, text: &str, x: usize, y: usize, color: u32, scale: usize) {
         for (i, c) in text.chars().enumerate() {
-            self.draw_char(buffer, width, c, x + i * 8, y, color, 0); 
+            self.draw_char_scaled(buffer, width, c.to_ascii_uppercase(), x + i * 8 * scale, y, color, 0, scale);
         }
     }
 }
 
+pub fn blend(color: u32, tint: u32, t: f32) -> u32 {
+    let mix = |shift: u32| {
+        let a = ((color >> shift) & 0xFF) as f32;
+        let b = ((tint >> shift) & 0xFF) as f32;
+        ((a + (b - a) * t) as u32) << shift
+    };
+    mix(16) | mix(8) | mix(0)
+}
+
 fn get_font_data(c: char) -> [u8; 8] {
     match c {
         'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11, 0x00],
@@ -140,6 +251,23 @@ fn get_font_data(c: char) -> [u8; 8] {
         '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C, 0x00],
         ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00, 0x00],
         ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00, 0x00],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '/' => [0x01, 0x01, 0x02, 0x04, 0x08, 0x10, 0x10, 0x00],
+        '>' => [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08, 0x00],
+        '<' => [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02, 0x00],
+        '[' => [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E, 0x00],
+        ']' => [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02, 0x00],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08, 0x00],
+        '\'' => [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04, 0x00],
+        '?' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04, 0x00],
+        '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03, 0x00],
         _ => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
     }
 }
\ No newline at end of file
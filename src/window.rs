@@ -0,0 +1,214 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use minifb::{InputCallback, MouseMode, Window, WindowOptions};
+
+use proyecto1::platform::{Key, KeyRepeat, MouseButton, Platform};
+
+// Recibe el texto que escribe el usuario desde minifb
+struct TextInput(Rc<RefCell<String>>);
+
+impl InputCallback for TextInput {
+    fn add_char(&mut self, uni_char: u32) {
+        if let Some(c) = char::from_u32(uni_char) {
+            // La tecla de la consola no se escribe
+            if !c.is_control() && c != '`' && c != '~' {
+                self.0.borrow_mut().push(c);
+            }
+        }
+    }
+}
+
+// minifb no sabe mover el puntero; con X11 se hace con Xlib sobre la misma
+// conexion de la ventana. En los demas sistemas `new` da None.
+#[cfg(not(any(target_os = "macos", target_os = "redox", windows)))]
+mod pointer {
+    use minifb::HasRawWindowHandle;
+    use raw_window_handle::RawWindowHandle;
+    use x11_dl::xlib::{Display, Xlib};
+
+    pub struct Pointer {
+        xlib: Xlib,
+        display: *mut Display,
+        window: u64,
+    }
+
+    impl Pointer {
+        pub fn new(window: &minifb::Window) -> Option<Self> {
+            let RawWindowHandle::Xlib(handle) = window.raw_window_handle() else {
+                return None;
+            };
+            let xlib = Xlib::open().ok()?;
+            Some(Pointer { xlib, display: handle.display as *mut Display, window: handle.window })
+        }
+
+        // Lleva el puntero a (x, y) dentro de la ventana
+        pub fn warp(&self, x: i32, y: i32) {
+            // SAFETY: `display` y `window` son los de la ventana de minifb,
+            // que vive mas que este `Pointer`
+            unsafe {
+                (self.xlib.XWarpPointer)(self.display, 0, self.window, 0, 0, 0, 0, x, y);
+                (self.xlib.XFlush)(self.display);
+            }
+        }
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "redox", windows))]
+mod pointer {
+    pub struct Pointer;
+
+    impl Pointer {
+        pub fn new(_window: &minifb::Window) -> Option<Self> {
+            None
+        }
+
+        pub fn warp(&self, _x: i32, _y: i32) {}
+    }
+}
+
+// La ventana del juego
+pub struct MinifbPlatform {
+    window: Window,
+    typed: Rc<RefCell<String>>,
+    last_mouse: Option<(f32, f32)>,
+    delta: (f32, f32),
+    pointer: Option<pointer::Pointer>,
+    grabbed: bool,
+}
+
+impl MinifbPlatform {
+    pub fn new(title: &str, width: usize, height: usize) -> Result<Self, String> {
+        let mut window = Window::new(title, width, height, WindowOptions { resize: true, ..WindowOptions::default() })
+            .map_err(|e| format!("Failed to open the window: {}", e))?;
+        let typed = Rc::new(RefCell::new(String::new()));
+        window.set_input_callback(Box::new(TextInput(typed.clone())));
+        let pointer = pointer::Pointer::new(&window);
+        Ok(MinifbPlatform { window, typed, last_mouse: None, delta: (0.0, 0.0), pointer, grabbed: false })
+    }
+
+    // minifb actualiza teclado y mouse al mostrar el cuadro; lo escrito
+    // antes de eso ya se leyo (o se descarta)
+    fn before_update(&mut self) {
+        self.typed.borrow_mut().clear();
+    }
+
+    fn after_update(&mut self) {
+        let mouse = self.window.get_mouse_pos(MouseMode::Pass);
+        self.delta = match (self.last_mouse, mouse) {
+            (Some((lx, ly)), Some((x, y))) => (x - lx, y - ly),
+            _ => (0.0, 0.0),
+        };
+        self.last_mouse = mouse;
+
+        // Retenido, el puntero vuelve al centro cada cuadro y el proximo
+        // delta se mide desde ahi, asi el giro no se corta en el borde
+        if let (true, Some(pointer)) = (self.grabbed && self.window.is_active(), &self.pointer) {
+            let (width, height) = self.window.get_size();
+            let center = (width as f32 / 2.0, height as f32 / 2.0);
+            pointer.warp(center.0 as i32, center.1 as i32);
+            self.last_mouse = Some(center);
+        }
+    }
+}
+
+fn to_minifb(key: Key) -> minifb::Key {
+    match key {
+        Key::W => minifb::Key::W,
+        Key::A => minifb::Key::A,
+        Key::S => minifb::Key::S,
+        Key::D => minifb::Key::D,
+        Key::E => minifb::Key::E,
+        Key::F => minifb::Key::F,
+        Key::M => minifb::Key::M,
+        Key::O => minifb::Key::O,
+        Key::P => minifb::Key::P,
+        Key::Q => minifb::Key::Q,
+        Key::Y => minifb::Key::Y,
+        Key::Z => minifb::Key::Z,
+        Key::Key1 => minifb::Key::Key1,
+        Key::Key2 => minifb::Key::Key2,
+        Key::Key3 => minifb::Key::Key3,
+        Key::Key4 => minifb::Key::Key4,
+        Key::Key5 => minifb::Key::Key5,
+        Key::Key6 => minifb::Key::Key6,
+        Key::Key7 => minifb::Key::Key7,
+        Key::Key8 => minifb::Key::Key8,
+        Key::Key9 => minifb::Key::Key9,
+        Key::Up => minifb::Key::Up,
+        Key::Down => minifb::Key::Down,
+        Key::Left => minifb::Key::Left,
+        Key::Right => minifb::Key::Right,
+        Key::Space => minifb::Key::Space,
+        Key::Enter => minifb::Key::Enter,
+        Key::Backspace => minifb::Key::Backspace,
+        Key::Tab => minifb::Key::Tab,
+        Key::Escape => minifb::Key::Escape,
+        Key::Backquote => minifb::Key::Backquote,
+        Key::F3 => minifb::Key::F3,
+        Key::LeftCtrl => minifb::Key::LeftCtrl,
+        Key::RightCtrl => minifb::Key::RightCtrl,
+        Key::LeftShift => minifb::Key::LeftShift,
+        Key::RightShift => minifb::Key::RightShift,
+    }
+}
+
+impl Platform for MinifbPlatform {
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.window.get_size()
+    }
+
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) {
+        self.before_update();
+        self.window.update_with_buffer(buffer, width, height).unwrap();
+        self.after_update();
+    }
+
+    fn update(&mut self) {
+        self.before_update();
+        self.window.update();
+        self.after_update();
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.window.is_key_down(to_minifb(key))
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        let repeat = match repeat {
+            KeyRepeat::Yes => minifb::KeyRepeat::Yes,
+            KeyRepeat::No => minifb::KeyRepeat::No,
+        };
+        self.window.is_key_pressed(to_minifb(key), repeat)
+    }
+
+    fn mouse_pos(&self) -> Option<(f32, f32)> {
+        self.window.get_mouse_pos(MouseMode::Discard)
+    }
+
+    fn mouse_delta(&self) -> (f32, f32) {
+        self.delta
+    }
+
+    fn is_mouse_down(&self, button: MouseButton) -> bool {
+        self.window.get_mouse_down(match button {
+            MouseButton::Left => minifb::MouseButton::Left,
+            MouseButton::Right => minifb::MouseButton::Right,
+        })
+    }
+
+    // Sin forma de recentrar el puntero no se oculta: el giro se corta en el
+    // borde de la ventana y con el cursor a la vista se entiende por que
+    fn set_cursor_grab(&mut self, grab: bool) {
+        self.grabbed = grab && self.pointer.is_some();
+        self.window.set_cursor_visibility(!self.grabbed);
+    }
+
+    fn take_text(&mut self) -> String {
+        std::mem::take(&mut *self.typed.borrow_mut())
+    }
+}
@@ -0,0 +1,87 @@
+// Partidas completas con `--headless`: el binario recibe un guion de
+// entradas y al salir imprime como termino la partida.
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+// Archivo temporal propio de cada prueba
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("proyecto1-{}-{}", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+// Corre una partida chica y devuelve lo que imprimio
+fn play(test: &str, args: &[&str], script: &str) -> String {
+    session(test, &[&["--skip-intro"], args].concat(), script)
+}
+
+// Como `play`, pero desde la pantalla de bienvenida
+fn session(test: &str, args: &[&str], script: &str) -> String {
+    let script = temp_file(&format!("{}.script", test), script);
+    let output = Command::new(env!("CARGO_BIN_EXE_proyecto1"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["play", "--headless", "--width", "160", "--height", "120"])
+        .arg("--script")
+        .arg(&script)
+        .args(args)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    assert!(output.status.success(), "game failed: {}{}", stdout, String::from_utf8_lossy(&output.stderr));
+    stdout
+}
+
+#[test]
+fn walking_around_keeps_the_game_going() {
+    let stdout = play("walk", &[], "0 down W\n8 up W\n8 down D\n12 close\n");
+    assert!(stdout.contains("Headless session ended after 12 frames: still playing"), "{}", stdout);
+}
+
+#[test]
+fn reaching_the_goal_wins_and_plays_the_victory_sting() {
+    // La meta de maze.txt esta en la celda (11, 7)
+    let exec = temp_file("goal.cfg", "tp 11.5 7.5\n");
+    let audio_log = std::env::temp_dir().join(format!("proyecto1-{}-cues.txt", std::process::id()));
+    let stdout = play("goal", &["--exec", exec.to_str().unwrap(), "--audio-log", audio_log.to_str().unwrap()], "5 close\n");
+    assert!(stdout.contains("reached the goal"), "{}", stdout);
+
+    let cues = fs::read_to_string(&audio_log).unwrap();
+    assert!(cues.lines().any(|line| line.ends_with(" sting.victory")), "{}", cues);
+}
+
+#[test]
+fn exec_of_a_running_script_is_skipped() {
+    // El script se llama a si mismo y despues sigue con el resto de sus lineas
+    let path = std::env::temp_dir().join(format!("proyecto1-{}-loop.cfg", std::process::id()));
+    let exec = temp_file("loop.cfg", &format!("exec {}\ntp 0.5 0.5\ntp 11.5 7.5\n", path.display()));
+    let stdout = play("loop", &["--exec", exec.to_str().unwrap()], "5 close\n");
+    assert!(stdout.contains("reached the goal"), "{}", stdout);
+}
+
+#[test]
+fn leaving_the_editor_with_unsaved_changes_needs_a_second_backspace() {
+    // Entra al editor, pinta el inicio del jugador en el centro y sale
+    let edit = "1 press E\n3 press 3\n4 button left down\n5 button left up\n6 press Backspace\n";
+    let stdout = session("editor-once", &[], &format!("{}8 close\n", edit));
+    assert!(stdout.contains("ended after 8 frames: in the editor"), "{}", stdout);
+
+    let stdout = session("editor-twice", &[], &format!("{}8 press Backspace\n10 close\n", edit));
+    assert!(stdout.contains("ended after 10 frames: on the welcome screen"), "{}", stdout);
+
+    // Sin cambios sale a la primera
+    let stdout = session("editor-clean", &[], "1 press E\n3 press Backspace\n5 close\n");
+    assert!(stdout.contains("ended after 5 frames: on the welcome screen"), "{}", stdout);
+}
+
+#[test]
+fn check_map_finds_maps_in_the_assets() {
+    let output = Command::new(env!("CARGO_BIN_EXE_proyecto1"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["check-map", "maze.txt"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}{}", stdout, String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("maze.txt: ok"), "{}", stdout);
+}